@@ -118,6 +118,59 @@ pub fn is_ci() -> bool {
         || has_env_var("CIRCLECI")
 }
 
+/// Check if colored output was disabled through the `NO_COLOR` convention
+pub fn is_no_color() -> bool {
+    env::var("NO_COLOR").is_ok_and(|v| !v.is_empty())
+}
+
+/// Check if stdout is a terminal that should receive ANSI colors
+///
+/// Returns `false` when `NO_COLOR` is set, `TERM` is `dumb` or stdout is not a
+/// terminal (e.g. redirected to a file or captured by CI).
+pub fn supports_color() -> bool {
+    use std::io::IsTerminal;
+
+    color_allowed(std::io::stdout().is_terminal())
+}
+
+/// Check if stderr is a terminal that should receive ANSI colors
+///
+/// Same rules as [`supports_color`], applied to stderr, which can be
+/// redirected independently (`app 2>err.log`).
+pub fn supports_color_stderr() -> bool {
+    use std::io::IsTerminal;
+
+    color_allowed(std::io::stderr().is_terminal())
+}
+
+/// Decide whether a stream should receive ANSI colors
+///
+/// `no_color` and `term` are the values of the `NO_COLOR` and `TERM` variables,
+/// `is_terminal` whether the stream is attached to a terminal.
+///
+/// # Examples
+///
+/// ```
+/// use mudssky_utils::env::color_decision;
+///
+/// assert!(color_decision(None, Some("xterm-256color"), true));
+/// assert!(!color_decision(Some("1"), Some("xterm-256color"), true));
+/// assert!(!color_decision(None, Some("dumb"), true));
+/// assert!(!color_decision(None, None, false));
+/// ```
+pub fn color_decision(no_color: Option<&str>, term: Option<&str>, is_terminal: bool) -> bool {
+    let no_color = no_color.is_some_and(|v| !v.is_empty());
+    !no_color && term != Some("dumb") && is_terminal
+}
+
+fn color_allowed(is_terminal: bool) -> bool {
+    color_decision(
+        env::var("NO_COLOR").ok().as_deref(),
+        env::var("TERM").ok().as_deref(),
+        is_terminal,
+    )
+}
+
 /// Get home directory path
 pub fn get_home_dir() -> Option<String> {
     dirs::home_dir().map(|path| path.to_string_lossy().to_string())
@@ -217,6 +217,21 @@ pub enum UtilsError {
     Other(#[from] Box<dyn std::error::Error + Send + Sync>),
 }
 
+impl UtilsError {
+    /// Get the name of the variant, e.g. `"Validation"`
+    pub fn kind(&self) -> &'static str {
+        match self {
+            UtilsError::Argument(_) => "Argument",
+            UtilsError::Validation(_) => "Validation",
+            UtilsError::Config(_) => "Config",
+            UtilsError::Network(_) => "Network",
+            UtilsError::Parse(_) => "Parse",
+            UtilsError::Io(_) => "Io",
+            UtilsError::Other(_) => "Other",
+        }
+    }
+}
+
 /// Result type alias for utils operations
 pub type UtilsResult<T> = Result<T, UtilsError>;
 
@@ -1,7 +1,8 @@
 //! Function utilities module
 //!
 //! This module provides utilities for function manipulation including debouncing,
-//! throttling, polling, and retry mechanisms.
+//! throttling, polling, retry mechanisms, circuit breaking, rate limiting and
+//! memoization.
 
 use std::future::Future;
 use std::sync::atomic::{AtomicBool, Ordering};
@@ -9,6 +10,25 @@ use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 use tokio::time::sleep;
 
+mod circuit;
+mod debounce;
+mod memoize;
+mod rate_limit;
+mod retry;
+mod throttle;
+
+pub use circuit::{
+    CircuitBreaker, CircuitBreakerOptions, CircuitBreakerStatus, CircuitState, FailureWindow,
+};
+pub use debounce::Debounced;
+pub use memoize::{AsyncMemoized, CacheStats, MemoizeOptions, Memoized};
+pub use rate_limit::{Gcra, KeyedRateLimiter, RateLimiter, SlidingWindowLog, TokenBucket};
+pub use retry::{
+    Backoff, RetryError, RetryPolicy, RetryStop, is_retryable_error, with_retry_policy,
+    with_retry_typed,
+};
+pub use throttle::Throttled;
+
 /// Error types for function utilities
 #[derive(Debug, Clone)]
 pub enum FunctionError {
@@ -20,6 +40,8 @@ pub enum FunctionError {
     PollingError(String),
     /// General error
     General(String),
+    /// Call rejected by an open circuit breaker
+    CircuitOpen(String),
 }
 
 impl std::fmt::Display for FunctionError {
@@ -29,6 +51,7 @@ impl std::fmt::Display for FunctionError {
             FunctionError::RetryExhausted(msg) => write!(f, "Retry exhausted: {msg}"),
             FunctionError::PollingError(msg) => write!(f, "Polling error: {msg}"),
             FunctionError::General(msg) => write!(f, "Function error: {msg}"),
+            FunctionError::CircuitOpen(msg) => write!(f, "Circuit open: {msg}"),
         }
     }
 }
@@ -54,6 +77,9 @@ impl Default for DebounceOptions {
 }
 
 /// Debounce controller
+///
+/// Each `execute` call waits on its own; use [`Debounced`] to collapse a burst
+/// of calls into a single invocation.
 #[derive(Debug)]
 pub struct Debouncer {
     last_call: Arc<Mutex<Option<Instant>>>,
@@ -160,6 +186,9 @@ impl Default for ThrottleOptions {
 }
 
 /// Throttle controller
+///
+/// Calls made within the window are rejected; use [`Throttled`] for trailing
+/// invocations and shared results.
 #[derive(Debug)]
 pub struct Throttler {
     last_execution: Arc<Mutex<Option<Instant>>>,
@@ -404,6 +433,9 @@ impl Default for RetryOptions {
 
 /// Execute a function with retry logic
 ///
+/// Waits a fixed delay between attempts; see [`with_retry_policy`] for backoff
+/// strategies and time budgets.
+///
 /// # Examples
 ///
 /// ```
@@ -425,26 +457,5 @@ where
     F: Fn() -> Fut,
     Fut: Future<Output = Result<T, Box<dyn std::error::Error + Send + Sync>>>,
 {
-    let mut retry_count = 0;
-    let mut last_error: Option<Box<dyn std::error::Error + Send + Sync>> = None;
-
-    while retry_count <= options.max_retries {
-        match func().await {
-            Ok(result) => return Ok(result),
-            Err(error) => {
-                last_error = Some(error);
-                retry_count += 1;
-
-                if retry_count <= options.max_retries && options.delay > Duration::from_millis(0) {
-                    sleep(options.delay).await;
-                }
-            }
-        }
-    }
-
-    Err(FunctionError::RetryExhausted(format!(
-        "Function failed after {} retries. Last error: {}",
-        options.max_retries,
-        last_error.map(|e| e.to_string()).unwrap_or_else(|| "Unknown error".to_string())
-    )))
+    with_retry_policy(func, &options.into()).await
 }
@@ -0,0 +1,417 @@
+//! Circuit breaker for async calls
+
+use super::FunctionError;
+use std::collections::VecDeque;
+use std::fmt;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// State of a [`CircuitBreaker`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    /// Calls pass through and failures are counted
+    Closed,
+    /// Calls fail fast without running
+    Open,
+    /// A limited number of trial calls decide whether to close again
+    HalfOpen,
+}
+
+impl fmt::Display for CircuitState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CircuitState::Closed => write!(f, "closed"),
+            CircuitState::Open => write!(f, "open"),
+            CircuitState::HalfOpen => write!(f, "half-open"),
+        }
+    }
+}
+
+/// Which failures count towards the threshold
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FailureWindow {
+    /// Failures among the last `n` calls
+    Count(usize),
+    /// Failures within the last period
+    Time(Duration),
+}
+
+/// Circuit breaker options
+#[derive(Debug, Clone)]
+pub struct CircuitBreakerOptions {
+    /// Failures within the window that open the circuit
+    pub failure_threshold: usize,
+    /// Rolling window the failures are counted over
+    pub window: FailureWindow,
+    /// How long the circuit stays open before allowing trial calls
+    pub open_duration: Duration,
+    /// Concurrent trial calls allowed while half-open
+    pub half_open_max_calls: usize,
+    /// Successful trial calls needed to close the circuit
+    pub success_threshold: usize,
+}
+
+impl Default for CircuitBreakerOptions {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 5,
+            window: FailureWindow::Count(10),
+            open_duration: Duration::from_secs(30),
+            half_open_max_calls: 1,
+            success_threshold: 1,
+        }
+    }
+}
+
+/// Circuit breaker status
+#[derive(Debug, Clone)]
+pub struct CircuitBreakerStatus {
+    /// Current state
+    pub state: CircuitState,
+    /// Failures within the current window
+    pub failure_count: usize,
+    /// Calls that were allowed to run
+    pub total_calls: u64,
+    /// Calls rejected without running
+    pub rejected_calls: u64,
+    /// Time left until trial calls are allowed, while open
+    pub open_remaining: Option<Duration>,
+}
+
+#[derive(Debug)]
+struct BreakerState {
+    state: CircuitState,
+    /// Outcomes for count windows (`true` = failure), failure times for time windows
+    outcomes: VecDeque<bool>,
+    failures: VecDeque<Instant>,
+    opened_at: Option<Instant>,
+    /// Bumped on every transition so outcomes of calls started earlier are ignored
+    generation: u64,
+    trials_in_flight: usize,
+    trial_successes: usize,
+    total_calls: u64,
+    rejected_calls: u64,
+}
+
+type TransitionCallback = dyn Fn(CircuitState, CircuitState) + Send + Sync;
+
+/// Stops calling a failing dependency for a while
+///
+/// Wraps async calls and counts failures over a rolling window. Once
+/// `failure_threshold` is reached the circuit opens and calls fail fast with
+/// [`FunctionError::CircuitOpen`]. After `open_duration` the circuit is
+/// half-open: trial calls run, and enough successes close it again while a
+/// failure re-opens it.
+///
+/// # Examples
+///
+/// ```
+/// use mudssky_utils::function::{CircuitBreaker, CircuitBreakerOptions, CircuitState};
+///
+/// # #[tokio::main]
+/// # async fn main() {
+/// let breaker = CircuitBreaker::new(CircuitBreakerOptions {
+///     failure_threshold: 2,
+///     ..Default::default()
+/// });
+///
+/// for _ in 0..2 {
+///     let _ = breaker
+///         .call(|| async { Err::<(), Box<dyn std::error::Error + Send + Sync>>("down".into()) })
+///         .await;
+/// }
+/// assert_eq!(breaker.state(), CircuitState::Open);
+///
+/// let result = breaker
+///     .call(|| async { Ok::<_, Box<dyn std::error::Error + Send + Sync>>(1) })
+///     .await;
+/// assert!(result.is_err());
+/// # }
+/// ```
+pub struct CircuitBreaker {
+    options: CircuitBreakerOptions,
+    state: Mutex<BreakerState>,
+    on_state_change: Option<Arc<TransitionCallback>>,
+}
+
+impl fmt::Debug for CircuitBreaker {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CircuitBreaker")
+            .field("options", &self.options)
+            .field("state", &self.state.lock().unwrap().state)
+            .finish()
+    }
+}
+
+impl CircuitBreaker {
+    /// Create a closed circuit breaker
+    pub fn new(options: CircuitBreakerOptions) -> Self {
+        Self {
+            options,
+            state: Mutex::new(BreakerState {
+                state: CircuitState::Closed,
+                outcomes: VecDeque::new(),
+                failures: VecDeque::new(),
+                opened_at: None,
+                generation: 0,
+                trials_in_flight: 0,
+                trial_successes: 0,
+                total_calls: 0,
+                rejected_calls: 0,
+            }),
+            on_state_change: None,
+        }
+    }
+
+    /// Call `callback` with the old and new state on every transition
+    pub fn on_state_change<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(CircuitState, CircuitState) + Send + Sync + 'static,
+    {
+        self.on_state_change = Some(Arc::new(callback));
+        self
+    }
+
+    /// Run a call through the breaker
+    ///
+    /// Any `Err` returned by the call counts as a failure. While the circuit
+    /// is open the call is not run and [`FunctionError::CircuitOpen`] is
+    /// converted into the caller's error type.
+    pub async fn call<F, Fut, T, E>(&self, func: F) -> Result<T, E>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+        E: From<FunctionError>,
+    {
+        let mut permit = self.acquire().map_err(E::from)?;
+        let result = func().await;
+        permit.complete(result.is_ok());
+        result
+    }
+
+    /// Get the current state
+    pub fn state(&self) -> CircuitState {
+        let mut state = self.state.lock().unwrap();
+        let transition = self.refresh(&mut state, Instant::now());
+        let current = state.state;
+        drop(state);
+        self.notify(transition);
+        current
+    }
+
+    /// Get the current status
+    pub fn status(&self) -> CircuitBreakerStatus {
+        let now = Instant::now();
+        let mut state = self.state.lock().unwrap();
+        let transition = self.refresh(&mut state, now);
+        let status = CircuitBreakerStatus {
+            state: state.state,
+            failure_count: self.failure_count(&mut state, now),
+            total_calls: state.total_calls,
+            rejected_calls: state.rejected_calls,
+            open_remaining: match state.state {
+                CircuitState::Open => state
+                    .opened_at
+                    .map(|at| self.options.open_duration.saturating_sub(now.duration_since(at))),
+                _ => None,
+            },
+        };
+        drop(state);
+        self.notify(transition);
+        status
+    }
+
+    /// Close the circuit and forget recorded failures
+    pub fn reset(&self) {
+        let mut state = self.state.lock().unwrap();
+        let transition = self.transition(&mut state, CircuitState::Closed);
+        state.outcomes.clear();
+        state.failures.clear();
+        drop(state);
+        self.notify(transition);
+    }
+}
+
+type Transition = Option<(CircuitState, CircuitState)>;
+
+impl CircuitBreaker {
+    fn acquire(&self) -> Result<Permit<'_>, FunctionError> {
+        let now = Instant::now();
+        let mut state = self.state.lock().unwrap();
+        let transition = self.refresh(&mut state, now);
+
+        let result = match state.state {
+            CircuitState::Closed => Ok(false),
+            CircuitState::HalfOpen if state.trials_in_flight < self.options.half_open_max_calls => {
+                state.trials_in_flight += 1;
+                Ok(true)
+            }
+            CircuitState::HalfOpen => Err(FunctionError::CircuitOpen(
+                "Circuit breaker is half-open and its trial calls are in flight".to_string(),
+            )),
+            CircuitState::Open => {
+                let remaining = state.opened_at.map_or(Duration::ZERO, |at| {
+                    self.options.open_duration.saturating_sub(now.duration_since(at))
+                });
+                Err(FunctionError::CircuitOpen(format!(
+                    "Circuit breaker is open, retry in {remaining:?}"
+                )))
+            }
+        };
+        match result {
+            Ok(_) => state.total_calls += 1,
+            Err(_) => state.rejected_calls += 1,
+        }
+        let generation = state.generation;
+        drop(state);
+        self.notify(transition);
+
+        result.map(|trial| Permit {
+            breaker: self,
+            generation,
+            trial,
+            done: false,
+        })
+    }
+
+    /// Record the outcome of a call that was allowed to run
+    fn record(&self, generation: u64, trial: bool, success: bool) {
+        let now = Instant::now();
+        let mut state = self.state.lock().unwrap();
+        if state.generation != generation {
+            // Calls started under a previous state do not affect the current one
+            return;
+        }
+        let transition = match (state.state, trial) {
+            (CircuitState::HalfOpen, true) => {
+                state.trials_in_flight = state.trials_in_flight.saturating_sub(1);
+                if !success {
+                    self.transition(&mut state, CircuitState::Open)
+                } else {
+                    state.trial_successes += 1;
+                    if state.trial_successes >= self.options.success_threshold.max(1) {
+                        self.transition(&mut state, CircuitState::Closed)
+                    } else {
+                        None
+                    }
+                }
+            }
+            (CircuitState::Closed, false) => {
+                self.push_outcome(&mut state, now, !success);
+                if !success
+                    && self.failure_count(&mut state, now) >= self.options.failure_threshold.max(1)
+                {
+                    self.transition(&mut state, CircuitState::Open)
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        };
+        drop(state);
+        self.notify(transition);
+    }
+
+    /// Release a trial slot of a call that was dropped before completing
+    fn abandon(&self, generation: u64, trial: bool) {
+        if trial {
+            let mut state = self.state.lock().unwrap();
+            if state.generation == generation && state.state == CircuitState::HalfOpen {
+                state.trials_in_flight = state.trials_in_flight.saturating_sub(1);
+            }
+        }
+    }
+
+    /// Move from open to half-open once the open duration has passed
+    fn refresh(&self, state: &mut BreakerState, now: Instant) -> Transition {
+        let expired = state
+            .opened_at
+            .is_some_and(|at| now.duration_since(at) >= self.options.open_duration);
+        if state.state == CircuitState::Open && expired {
+            self.transition(state, CircuitState::HalfOpen)
+        } else {
+            None
+        }
+    }
+
+    fn transition(&self, state: &mut BreakerState, to: CircuitState) -> Transition {
+        let from = state.state;
+        if from == to {
+            return None;
+        }
+        state.state = to;
+        state.generation = state.generation.wrapping_add(1);
+        state.trials_in_flight = 0;
+        state.trial_successes = 0;
+        match to {
+            CircuitState::Open => state.opened_at = Some(Instant::now()),
+            CircuitState::Closed => {
+                state.opened_at = None;
+                state.outcomes.clear();
+                state.failures.clear();
+            }
+            CircuitState::HalfOpen => {}
+        }
+        Some((from, to))
+    }
+
+    fn notify(&self, transition: Transition) {
+        if let (Some((from, to)), Some(callback)) = (transition, &self.on_state_change) {
+            callback(from, to);
+        }
+    }
+
+    fn push_outcome(&self, state: &mut BreakerState, now: Instant, failure: bool) {
+        match self.options.window {
+            FailureWindow::Count(size) => {
+                state.outcomes.push_back(failure);
+                while state.outcomes.len() > size.max(1) {
+                    state.outcomes.pop_front();
+                }
+            }
+            FailureWindow::Time(_) => {
+                if failure {
+                    state.failures.push_back(now);
+                }
+            }
+        }
+    }
+
+    fn failure_count(&self, state: &mut BreakerState, now: Instant) -> usize {
+        match self.options.window {
+            FailureWindow::Count(_) => state.outcomes.iter().filter(|&&failed| failed).count(),
+            FailureWindow::Time(period) => {
+                while state.failures.front().is_some_and(|at| now.duration_since(*at) > period) {
+                    state.failures.pop_front();
+                }
+                state.failures.len()
+            }
+        }
+    }
+}
+
+/// Tracks a running call so dropped calls release their trial slot
+struct Permit<'a> {
+    breaker: &'a CircuitBreaker,
+    /// Breaker generation the call started in
+    generation: u64,
+    trial: bool,
+    done: bool,
+}
+
+impl Permit<'_> {
+    fn complete(&mut self, success: bool) {
+        self.done = true;
+        self.breaker.record(self.generation, self.trial, success);
+    }
+}
+
+impl Drop for Permit<'_> {
+    fn drop(&mut self) {
+        if !self.done {
+            self.breaker.abandon(self.generation, self.trial);
+        }
+    }
+}
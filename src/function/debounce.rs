@@ -0,0 +1,270 @@
+//! Debounced callbacks that collapse bursts of calls
+
+use super::DebounceOptions;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time::{Instant, sleep_until};
+
+type Callback<A> = dyn Fn(A) -> Pin<Box<dyn Future<Output = ()> + Send>> + Send + Sync;
+
+struct DebounceState<A> {
+    last_args: Option<A>,
+    last_call: Option<Instant>,
+    last_invoke: Option<Instant>,
+    max_wait: Option<Duration>,
+    /// Bumped whenever the pending timer is stopped, so its task exits
+    generation: u64,
+    timer_active: bool,
+    invocations: u64,
+}
+
+struct Shared<A> {
+    callback: Box<Callback<A>>,
+    wait: Duration,
+    options: DebounceOptions,
+    state: Mutex<DebounceState<A>>,
+}
+
+/// A callback that only runs once a burst of calls has settled
+///
+/// Follows lodash `debounce` semantics: the callback runs `wait` after the last
+/// call with the latest arguments (trailing edge), optionally also on the first
+/// call of a burst (leading edge), and at least every `max_wait` while calls
+/// keep coming. Clones share the same state, so it can be handed to many tasks.
+///
+/// Must be used inside a Tokio runtime; invocations run as spawned tasks.
+///
+/// # Examples
+///
+/// ```
+/// use mudssky_utils::function::{DebounceOptions, Debounced};
+/// use std::sync::Arc;
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+/// use std::time::Duration;
+///
+/// # #[tokio::main]
+/// # async fn main() {
+/// let saved = Arc::new(AtomicUsize::new(0));
+/// let saved_clone = saved.clone();
+/// let save = Debounced::new(Duration::from_millis(20), DebounceOptions::default(), move |n: usize| {
+///     let saved = saved_clone.clone();
+///     async move {
+///         saved.store(n, Ordering::SeqCst);
+///     }
+/// });
+///
+/// for n in 1..=5 {
+///     save.call(n);
+/// }
+/// save.flush().await;
+/// assert_eq!(saved.load(Ordering::SeqCst), 5);
+/// assert_eq!(save.invocation_count(), 1);
+/// # }
+/// ```
+pub struct Debounced<A> {
+    shared: Arc<Shared<A>>,
+}
+
+impl<A> Clone for Debounced<A> {
+    fn clone(&self) -> Self {
+        Self {
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl<A> std::fmt::Debug for Debounced<A> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let state = self.shared.state.lock().unwrap();
+        f.debug_struct("Debounced")
+            .field("wait", &self.shared.wait)
+            .field("max_wait", &state.max_wait)
+            .field("options", &self.shared.options)
+            .field("pending", &state.timer_active)
+            .finish()
+    }
+}
+
+impl<A: Send + 'static> Debounced<A> {
+    /// Wrap an async callback
+    pub fn new<F, Fut>(wait: Duration, options: DebounceOptions, callback: F) -> Self
+    where
+        F: Fn(A) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        Self {
+            shared: Arc::new(Shared {
+                callback: Box::new(move |args| Box::pin(callback(args))),
+                wait,
+                options,
+                state: Mutex::new(DebounceState {
+                    last_args: None,
+                    last_call: None,
+                    last_invoke: None,
+                    max_wait: None,
+                    generation: 0,
+                    timer_active: false,
+                    invocations: 0,
+                }),
+            }),
+        }
+    }
+
+    /// Invoke the callback at least once every `max_wait` during a burst
+    ///
+    /// Values below `wait` are raised to `wait`. The setting is shared by all
+    /// clones.
+    pub fn with_max_wait(self, max_wait: Duration) -> Self {
+        self.shared.state.lock().unwrap().max_wait = Some(max_wait.max(self.shared.wait));
+        self
+    }
+
+    /// Record a call with its arguments
+    pub fn call(&self, args: A) {
+        let now = Instant::now();
+        let mut state = self.shared.state.lock().unwrap();
+        let is_invoking = self.should_invoke(&state, now);
+
+        state.last_args = Some(args);
+        state.last_call = Some(now);
+
+        if is_invoking {
+            if !state.timer_active {
+                // Leading edge of a new burst
+                state.last_invoke = Some(now);
+                self.start_timer(&mut state);
+                if self.shared.options.leading {
+                    self.invoke(&mut state, now);
+                }
+                return;
+            }
+            if state.max_wait.is_some() {
+                self.invoke(&mut state, now);
+                return;
+            }
+        }
+        if !state.timer_active {
+            self.start_timer(&mut state);
+        }
+    }
+
+    /// Run a pending trailing call right away
+    ///
+    /// Returns `true` when the callback was invoked; the invocation has
+    /// finished when the returned future completes.
+    pub async fn flush(&self) -> bool {
+        let args = {
+            let mut state = self.shared.state.lock().unwrap();
+            if !state.timer_active {
+                return false;
+            }
+            self.stop_timer(&mut state);
+            self.take_trailing(&mut state, Instant::now())
+        };
+        match args {
+            Some(args) => {
+                (self.shared.callback)(args).await;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Drop any pending call and reset the debouncer to its initial state
+    pub fn cancel(&self) {
+        let mut state = self.shared.state.lock().unwrap();
+        self.stop_timer(&mut state);
+        state.last_args = None;
+        state.last_call = None;
+        state.last_invoke = None;
+    }
+
+    /// Check whether a trailing invocation is scheduled
+    pub fn is_pending(&self) -> bool {
+        self.shared.state.lock().unwrap().timer_active
+    }
+
+    /// Number of times the callback has been invoked
+    pub fn invocation_count(&self) -> u64 {
+        self.shared.state.lock().unwrap().invocations
+    }
+}
+
+impl<A: Send + 'static> Debounced<A> {
+    fn should_invoke(&self, state: &DebounceState<A>, now: Instant) -> bool {
+        let Some(last_call) = state.last_call else {
+            return true;
+        };
+        now.duration_since(last_call) >= self.shared.wait
+            || state.max_wait.is_some_and(|max_wait| {
+                state.last_invoke.is_some_and(|at| now.duration_since(at) >= max_wait)
+            })
+    }
+
+    /// Time until the timer should fire
+    fn remaining_wait(&self, state: &DebounceState<A>, now: Instant) -> Duration {
+        let since_call = state.last_call.map_or(Duration::ZERO, |at| now.duration_since(at));
+        let wait = self.shared.wait.saturating_sub(since_call);
+        match (state.max_wait, state.last_invoke) {
+            (Some(max_wait), Some(at)) => wait.min(max_wait.saturating_sub(now.duration_since(at))),
+            _ => wait,
+        }
+    }
+
+    fn start_timer(&self, state: &mut DebounceState<A>) {
+        state.timer_active = true;
+        let generation = state.generation;
+        let this = self.clone();
+        tokio::spawn(async move { this.run_timer(generation).await });
+    }
+
+    fn stop_timer(&self, state: &mut DebounceState<A>) {
+        state.generation = state.generation.wrapping_add(1);
+        state.timer_active = false;
+    }
+
+    async fn run_timer(self, generation: u64) {
+        loop {
+            let deadline = {
+                let mut state = self.shared.state.lock().unwrap();
+                if state.generation != generation {
+                    return;
+                }
+                let now = Instant::now();
+                if self.should_invoke(&state, now) {
+                    self.stop_timer(&mut state);
+                    Ok(self.take_trailing(&mut state, now))
+                } else {
+                    Err(now + self.remaining_wait(&state, now))
+                }
+            };
+            match deadline {
+                Ok(Some(args)) => return (self.shared.callback)(args).await,
+                Ok(None) => return,
+                Err(deadline) => sleep_until(deadline).await,
+            }
+        }
+    }
+
+    /// Take the arguments for a trailing invocation, if one is due
+    fn take_trailing(&self, state: &mut DebounceState<A>, now: Instant) -> Option<A> {
+        if self.shared.options.trailing && state.last_args.is_some() {
+            state.last_invoke = Some(now);
+            state.invocations += 1;
+            state.last_args.take()
+        } else {
+            state.last_args = None;
+            None
+        }
+    }
+
+    fn invoke(&self, state: &mut DebounceState<A>, now: Instant) {
+        if let Some(args) = state.last_args.take() {
+            state.last_invoke = Some(now);
+            state.invocations += 1;
+            tokio::spawn((self.shared.callback)(args));
+        }
+    }
+}
@@ -0,0 +1,395 @@
+//! Memoization with LRU eviction, TTL and single-flight async calls
+
+use std::collections::{BTreeMap, HashMap};
+use std::future::Future;
+use std::hash::Hash;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::OnceCell;
+
+/// Memoization options
+#[derive(Debug, Clone)]
+pub struct MemoizeOptions {
+    /// Maximum number of cached results; the least recently used is evicted
+    pub capacity: usize,
+    /// How long a cached result stays valid after it was computed
+    pub ttl: Option<Duration>,
+}
+
+impl Default for MemoizeOptions {
+    fn default() -> Self {
+        Self {
+            capacity: 1024,
+            ttl: None,
+        }
+    }
+}
+
+/// Cache statistics
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    /// Calls answered from the cache
+    pub hits: u64,
+    /// Calls that started a computation
+    pub misses: u64,
+    /// Calls that joined a computation already in flight
+    pub coalesced: u64,
+    /// Entries dropped to stay within capacity
+    pub evictions: u64,
+    /// Entries dropped because their TTL passed
+    pub expirations: u64,
+    /// Entries currently cached
+    pub size: usize,
+}
+
+impl CacheStats {
+    /// Share of calls that did not start a computation
+    pub fn hit_rate(&self) -> f64 {
+        let total = self.hits + self.misses + self.coalesced;
+        if total == 0 {
+            0.0
+        } else {
+            (self.hits + self.coalesced) as f64 / total as f64
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Slot<V> {
+    value: V,
+    expires_at: Option<Instant>,
+    tick: u64,
+}
+
+/// LRU map with optional per-entry expiry
+#[derive(Debug)]
+struct LruCache<K, V> {
+    entries: HashMap<K, Slot<V>>,
+    /// Keys by last use, oldest first
+    order: BTreeMap<u64, K>,
+    tick: u64,
+    options: MemoizeOptions,
+    stats: CacheStats,
+}
+
+impl<K: Hash + Eq + Clone, V: Clone> LruCache<K, V> {
+    fn new(options: MemoizeOptions) -> Self {
+        Self {
+            entries: HashMap::new(),
+            order: BTreeMap::new(),
+            tick: 0,
+            options,
+            stats: CacheStats::default(),
+        }
+    }
+
+    /// Look up a live entry and mark it as recently used
+    fn get(&mut self, key: &K, now: Instant) -> Option<V> {
+        let expired = self.entries.get(key)?.expires_at.is_some_and(|at| now >= at);
+        if expired {
+            self.remove(key);
+            self.stats.expirations += 1;
+            return None;
+        }
+
+        self.tick += 1;
+        let tick = self.tick;
+        let slot = self.entries.get_mut(key)?;
+        self.order.remove(&slot.tick);
+        slot.tick = tick;
+        self.order.insert(tick, key.clone());
+        Some(slot.value.clone())
+    }
+
+    fn insert(&mut self, key: K, value: V, now: Instant) {
+        if self.options.capacity == 0 {
+            return;
+        }
+        self.remove(&key);
+        while self.entries.len() >= self.options.capacity {
+            let Some((_, oldest)) = self.order.pop_first() else {
+                break;
+            };
+            self.entries.remove(&oldest);
+            self.stats.evictions += 1;
+        }
+
+        self.tick += 1;
+        self.order.insert(self.tick, key.clone());
+        self.entries.insert(
+            key,
+            Slot {
+                value,
+                expires_at: self.options.ttl.map(|ttl| now + ttl),
+                tick: self.tick,
+            },
+        );
+    }
+
+    fn remove(&mut self, key: &K) -> bool {
+        match self.entries.remove(key) {
+            Some(slot) => {
+                self.order.remove(&slot.tick);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+        self.order.clear();
+    }
+
+    fn stats(&self) -> CacheStats {
+        CacheStats {
+            size: self.entries.len(),
+            ..self.stats.clone()
+        }
+    }
+}
+
+/// A synchronous function whose results are cached by argument
+///
+/// Results are computed outside the cache lock, so a memoized function may
+/// call itself recursively. Threads that miss on the same key at the same
+/// time each compute the result; use [`AsyncMemoized`] for single-flight
+/// behaviour. A result whose key is invalidated or cleared while it is being
+/// computed is returned to its caller but not cached.
+///
+/// # Examples
+///
+/// ```
+/// use mudssky_utils::function::{MemoizeOptions, Memoized};
+///
+/// let square = Memoized::new(MemoizeOptions::default(), |n: &u64| n * n);
+/// assert_eq!(square.call(12), 144);
+/// assert_eq!(square.call(12), 144);
+/// assert_eq!(square.stats().hits, 1);
+/// assert_eq!(square.stats().misses, 1);
+/// ```
+pub struct Memoized<A, T> {
+    func: Box<dyn Fn(&A) -> T + Send + Sync>,
+    state: Mutex<SyncState<A, T>>,
+}
+
+struct SyncState<A, T> {
+    cache: LruCache<A, T>,
+    /// Token of the latest computation started for each key
+    computing: HashMap<A, u64>,
+    next_token: u64,
+}
+
+impl<A, T> std::fmt::Debug for Memoized<A, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let state = self.state.lock().unwrap();
+        f.debug_struct("Memoized")
+            .field("options", &state.cache.options)
+            .field("size", &state.cache.entries.len())
+            .finish()
+    }
+}
+
+impl<A, T> Memoized<A, T>
+where
+    A: Hash + Eq + Clone,
+    T: Clone,
+{
+    /// Wrap a function
+    pub fn new<F>(options: MemoizeOptions, func: F) -> Self
+    where
+        F: Fn(&A) -> T + Send + Sync + 'static,
+    {
+        Self {
+            func: Box::new(func),
+            state: Mutex::new(SyncState {
+                cache: LruCache::new(options),
+                computing: HashMap::new(),
+                next_token: 0,
+            }),
+        }
+    }
+
+    /// Return the cached result for `args`, computing it on a miss
+    pub fn call(&self, args: A) -> T {
+        let token = {
+            let mut state = self.state.lock().unwrap();
+            if let Some(value) = state.cache.get(&args, Instant::now()) {
+                state.cache.stats.hits += 1;
+                return value;
+            }
+            state.cache.stats.misses += 1;
+            state.next_token += 1;
+            let token = state.next_token;
+            state.computing.insert(args.clone(), token);
+            token
+        };
+
+        let value = (self.func)(&args);
+        let mut state = self.state.lock().unwrap();
+        // Skip the insert when the key was invalidated or recomputed meanwhile
+        if state.computing.get(&args) == Some(&token) {
+            state.computing.remove(&args);
+            state.cache.insert(args, value.clone(), Instant::now());
+        }
+        value
+    }
+
+    /// Drop the cached result for `args`; returns whether one was cached
+    pub fn invalidate(&self, args: &A) -> bool {
+        let mut state = self.state.lock().unwrap();
+        state.computing.remove(args);
+        state.cache.remove(args)
+    }
+
+    /// Drop every cached result
+    pub fn clear(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.computing.clear();
+        state.cache.clear();
+    }
+
+    /// Get the cache statistics
+    pub fn stats(&self) -> CacheStats {
+        self.state.lock().unwrap().cache.stats()
+    }
+}
+
+type Callback<A, T> = dyn Fn(A) -> Pin<Box<dyn Future<Output = T> + Send>> + Send + Sync;
+
+struct AsyncState<A, T> {
+    cache: LruCache<A, T>,
+    in_flight: HashMap<A, Arc<OnceCell<T>>>,
+}
+
+/// An async function whose results are cached by argument
+///
+/// Concurrent callers with the same arguments share a single in-flight
+/// computation. If the caller running it is cancelled, one of the waiting
+/// callers takes over. Clones share the same cache.
+///
+/// # Examples
+///
+/// ```
+/// use mudssky_utils::function::{AsyncMemoized, MemoizeOptions};
+/// use std::time::Duration;
+///
+/// # #[tokio::main]
+/// # async fn main() {
+/// let options = MemoizeOptions {
+///     capacity: 100,
+///     ttl: Some(Duration::from_secs(60)),
+/// };
+/// let lookup = AsyncMemoized::new(options, |user_id: u32| async move {
+///     format!("user-{user_id}")
+/// });
+///
+/// let (a, b) = tokio::join!(lookup.call(7), lookup.call(7));
+/// assert_eq!(a, "user-7");
+/// assert_eq!(b, "user-7");
+/// assert_eq!(lookup.stats().misses, 1);
+/// # }
+/// ```
+pub struct AsyncMemoized<A, T> {
+    func: Arc<Callback<A, T>>,
+    state: Arc<Mutex<AsyncState<A, T>>>,
+}
+
+impl<A, T> Clone for AsyncMemoized<A, T> {
+    fn clone(&self) -> Self {
+        Self {
+            func: self.func.clone(),
+            state: self.state.clone(),
+        }
+    }
+}
+
+impl<A, T> std::fmt::Debug for AsyncMemoized<A, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let state = self.state.lock().unwrap();
+        f.debug_struct("AsyncMemoized")
+            .field("options", &state.cache.options)
+            .field("size", &state.cache.entries.len())
+            .field("in_flight", &state.in_flight.len())
+            .finish()
+    }
+}
+
+impl<A, T> AsyncMemoized<A, T>
+where
+    A: Hash + Eq + Clone + Send + 'static,
+    T: Clone + Send + Sync + 'static,
+{
+    /// Wrap an async function
+    pub fn new<F, Fut>(options: MemoizeOptions, func: F) -> Self
+    where
+        F: Fn(A) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = T> + Send + 'static,
+    {
+        Self {
+            func: Arc::new(move |args| Box::pin(func(args))),
+            state: Arc::new(Mutex::new(AsyncState {
+                cache: LruCache::new(options),
+                in_flight: HashMap::new(),
+            })),
+        }
+    }
+
+    /// Return the cached result for `args`, computing it at most once on a miss
+    pub async fn call(&self, args: A) -> T {
+        let cell = {
+            let mut state = self.state.lock().unwrap();
+            if let Some(value) = state.cache.get(&args, Instant::now()) {
+                state.cache.stats.hits += 1;
+                return value;
+            }
+            match state.in_flight.get(&args) {
+                Some(cell) => {
+                    let cell = cell.clone();
+                    state.cache.stats.coalesced += 1;
+                    cell
+                }
+                None => {
+                    let cell = Arc::new(OnceCell::new());
+                    state.in_flight.insert(args.clone(), cell.clone());
+                    state.cache.stats.misses += 1;
+                    cell
+                }
+            }
+        };
+
+        let value = cell.get_or_init(|| (self.func)(args.clone())).await.clone();
+
+        let mut state = self.state.lock().unwrap();
+        // The first caller to finish moves the result into the cache, unless
+        // the key was invalidated in the meantime
+        if state.in_flight.get(&args).is_some_and(|c| Arc::ptr_eq(c, &cell)) {
+            state.in_flight.remove(&args);
+            state.cache.insert(args, value.clone(), Instant::now());
+        }
+        value
+    }
+
+    /// Drop the cached result for `args`; returns whether one was cached
+    ///
+    /// A computation in flight for `args` still completes for its callers but
+    /// its result is not cached.
+    pub fn invalidate(&self, args: &A) -> bool {
+        let mut state = self.state.lock().unwrap();
+        let in_flight = state.in_flight.remove(args).is_some();
+        state.cache.remove(args) || in_flight
+    }
+
+    /// Drop every cached result
+    pub fn clear(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.in_flight.clear();
+        state.cache.clear();
+    }
+
+    /// Get the cache statistics
+    pub fn stats(&self) -> CacheStats {
+        self.state.lock().unwrap().cache.stats()
+    }
+}
@@ -0,0 +1,387 @@
+//! Rate limiters that shape calls instead of dropping them
+
+use super::FunctionError;
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::hash::Hash;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time::{Instant, sleep};
+
+/// Common interface of the rate limiters
+///
+/// Waiting callers are not queued: after sleeping they race for permits again,
+/// so under contention the order in which they are served is not guaranteed.
+pub trait RateLimiter: Send + Sync {
+    /// Largest number of permits a single call can ever obtain
+    fn capacity(&self) -> u32;
+
+    /// Take `n` permits if they are available now
+    ///
+    /// Returns how long to wait before they could be available otherwise, or
+    /// `Duration::MAX` when `n` exceeds the capacity.
+    fn check(&self, n: u32) -> Result<(), Duration>;
+
+    /// Take `n` permits without waiting
+    fn try_acquire(&self, n: u32) -> bool {
+        self.check(n).is_ok()
+    }
+
+    /// Wait until `n` permits are available and take them
+    ///
+    /// Fails right away when `n` exceeds [`RateLimiter::capacity`].
+    fn acquire(&self, n: u32) -> impl Future<Output = Result<(), FunctionError>> + Send
+    where
+        Self: Sized,
+    {
+        acquire_from(self, n)
+    }
+}
+
+async fn acquire_from<L: RateLimiter + ?Sized>(limiter: &L, n: u32) -> Result<(), FunctionError> {
+    if n > limiter.capacity() {
+        return Err(FunctionError::General(format!(
+            "Cannot acquire {n} permits from a rate limiter with capacity {}",
+            limiter.capacity()
+        )));
+    }
+    loop {
+        match limiter.check(n) {
+            Ok(()) => return Ok(()),
+            Err(wait) => sleep(wait).await,
+        }
+    }
+}
+
+fn per_second(rate: u32, period: Duration) -> f64 {
+    assert!(
+        rate > 0 && !period.is_zero(),
+        "rate limiter needs a positive rate and period"
+    );
+    f64::from(rate) / period.as_secs_f64()
+}
+
+#[derive(Debug)]
+struct BucketState {
+    tokens: f64,
+    updated: Instant,
+}
+
+/// Token bucket refilled continuously at `rate` permits per `period`
+///
+/// Holds up to `burst` permits, so idle time allows short bursts above the
+/// average rate.
+///
+/// # Examples
+///
+/// ```
+/// use mudssky_utils::function::{RateLimiter, TokenBucket};
+/// use std::time::Duration;
+///
+/// # #[tokio::main]
+/// # async fn main() {
+/// let limiter = TokenBucket::new(10, Duration::from_secs(1), 2);
+/// assert!(limiter.try_acquire(2));
+/// assert!(!limiter.try_acquire(1));
+/// // Waits about 100ms for the next token
+/// limiter.acquire(1).await.unwrap();
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct TokenBucket {
+    rate: f64,
+    burst: u32,
+    state: Mutex<BucketState>,
+}
+
+impl TokenBucket {
+    /// Create a full bucket
+    ///
+    /// # Panics
+    ///
+    /// Panics when `rate` or `period` is zero.
+    pub fn new(rate: u32, period: Duration, burst: u32) -> Self {
+        let burst = burst.max(1);
+        Self {
+            rate: per_second(rate, period),
+            burst,
+            state: Mutex::new(BucketState {
+                tokens: f64::from(burst),
+                updated: Instant::now(),
+            }),
+        }
+    }
+
+    /// Number of whole permits available now
+    pub fn available(&self) -> u32 {
+        let mut state = self.state.lock().unwrap();
+        self.refill(&mut state, Instant::now());
+        state.tokens as u32
+    }
+
+    fn refill(&self, state: &mut BucketState, now: Instant) {
+        let elapsed = now.saturating_duration_since(state.updated).as_secs_f64();
+        state.tokens = (state.tokens + elapsed * self.rate).min(f64::from(self.burst));
+        state.updated = now;
+    }
+}
+
+impl RateLimiter for TokenBucket {
+    fn capacity(&self) -> u32 {
+        self.burst
+    }
+
+    fn check(&self, n: u32) -> Result<(), Duration> {
+        if n > self.burst {
+            return Err(Duration::MAX);
+        }
+        let mut state = self.state.lock().unwrap();
+        self.refill(&mut state, Instant::now());
+        let needed = f64::from(n);
+        if state.tokens >= needed {
+            state.tokens -= needed;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((needed - state.tokens) / self.rate))
+        }
+    }
+}
+
+/// Generic cell rate algorithm limiter
+///
+/// Spaces permits evenly at `rate` per `period` while tolerating bursts of up to
+/// `burst` permits. Behaves like a token bucket but only stores one timestamp.
+#[derive(Debug)]
+pub struct Gcra {
+    emission_interval: Duration,
+    burst: u32,
+    start: Instant,
+    /// Theoretical arrival time, as an offset from `start`
+    tat: Mutex<Duration>,
+}
+
+impl Gcra {
+    /// Create a limiter that allows a full burst right away
+    ///
+    /// # Panics
+    ///
+    /// Panics when `rate` or `period` is zero.
+    pub fn new(rate: u32, period: Duration, burst: u32) -> Self {
+        Self {
+            emission_interval: Duration::from_secs_f64(1.0 / per_second(rate, period)),
+            burst: burst.max(1),
+            start: Instant::now(),
+            tat: Mutex::new(Duration::ZERO),
+        }
+    }
+}
+
+impl RateLimiter for Gcra {
+    fn capacity(&self) -> u32 {
+        self.burst
+    }
+
+    fn check(&self, n: u32) -> Result<(), Duration> {
+        if n > self.burst {
+            return Err(Duration::MAX);
+        }
+        let now = self.start.elapsed();
+        let mut tat = self.tat.lock().unwrap();
+        let new_tat = (*tat).max(now) + self.emission_interval * n;
+        let allow_at = new_tat.saturating_sub(self.emission_interval * self.burst);
+        if now >= allow_at {
+            *tat = new_tat;
+            Ok(())
+        } else {
+            Err(allow_at - now)
+        }
+    }
+}
+
+/// Allows at most `limit` permits within any sliding `window`
+///
+/// Remembers the time of every permit, so it is exact but uses memory
+/// proportional to `limit`.
+#[derive(Debug)]
+pub struct SlidingWindowLog {
+    limit: u32,
+    window: Duration,
+    log: Mutex<VecDeque<Instant>>,
+}
+
+impl SlidingWindowLog {
+    /// Create an empty log
+    ///
+    /// # Panics
+    ///
+    /// Panics when `limit` or `window` is zero.
+    pub fn new(limit: u32, window: Duration) -> Self {
+        assert!(
+            limit > 0 && !window.is_zero(),
+            "rate limiter needs a positive limit and window"
+        );
+        Self {
+            limit,
+            window,
+            log: Mutex::new(VecDeque::new()),
+        }
+    }
+}
+
+impl RateLimiter for SlidingWindowLog {
+    fn capacity(&self) -> u32 {
+        self.limit
+    }
+
+    fn check(&self, n: u32) -> Result<(), Duration> {
+        if n > self.limit {
+            return Err(Duration::MAX);
+        }
+        let now = Instant::now();
+        let mut log = self.log.lock().unwrap();
+        while log.front().is_some_and(|at| now.duration_since(*at) >= self.window) {
+            log.pop_front();
+        }
+
+        let n = n as usize;
+        let limit = self.limit as usize;
+        if log.len() + n <= limit {
+            log.extend(std::iter::repeat_n(now, n));
+            Ok(())
+        } else {
+            // Wait until enough of the oldest permits have left the window
+            let expiring = log.len() + n - limit;
+            let until = log[expiring - 1] + self.window;
+            Err(until.saturating_duration_since(now).max(Duration::from_millis(1)))
+        }
+    }
+}
+
+#[derive(Debug)]
+struct KeyedEntry<L> {
+    limiter: Arc<L>,
+    last_used: Instant,
+}
+
+#[derive(Debug)]
+struct KeyedState<K, L> {
+    entries: HashMap<K, KeyedEntry<L>>,
+    last_sweep: Instant,
+}
+
+/// One rate limiter per key, e.g. per user or IP address
+///
+/// Limiters are created on first use and evicted after `idle_timeout` without
+/// calls. Choose an idle timeout at least as long as it takes a limiter to
+/// recover fully, so eviction never grants extra permits.
+///
+/// # Examples
+///
+/// ```
+/// use mudssky_utils::function::{KeyedRateLimiter, TokenBucket};
+/// use std::time::Duration;
+///
+/// let limiter = KeyedRateLimiter::new(Duration::from_secs(60), || {
+///     TokenBucket::new(5, Duration::from_secs(1), 5)
+/// });
+/// assert!(limiter.try_acquire(&"10.0.0.1", 5));
+/// assert!(!limiter.try_acquire(&"10.0.0.1", 1));
+/// assert!(limiter.try_acquire(&"10.0.0.2", 1));
+/// ```
+pub struct KeyedRateLimiter<K, L> {
+    factory: Box<dyn Fn() -> L + Send + Sync>,
+    idle_timeout: Duration,
+    state: Mutex<KeyedState<K, L>>,
+}
+
+impl<K, L> std::fmt::Debug for KeyedRateLimiter<K, L> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("KeyedRateLimiter")
+            .field("idle_timeout", &self.idle_timeout)
+            .field("keys", &self.state.lock().unwrap().entries.len())
+            .finish()
+    }
+}
+
+impl<K, L> KeyedRateLimiter<K, L>
+where
+    K: Eq + Hash + Clone,
+    L: RateLimiter,
+{
+    /// Create a keyed limiter that builds limiters with `factory`
+    pub fn new<F>(idle_timeout: Duration, factory: F) -> Self
+    where
+        F: Fn() -> L + Send + Sync + 'static,
+    {
+        Self {
+            factory: Box::new(factory),
+            idle_timeout,
+            state: Mutex::new(KeyedState {
+                entries: HashMap::new(),
+                last_sweep: Instant::now(),
+            }),
+        }
+    }
+
+    /// Take `n` permits for `key` if they are available now
+    ///
+    /// Returns how long to wait before they could be available otherwise.
+    pub fn check(&self, key: &K, n: u32) -> Result<(), Duration> {
+        self.limiter(key).check(n)
+    }
+
+    /// Take `n` permits for `key` without waiting
+    pub fn try_acquire(&self, key: &K, n: u32) -> bool {
+        self.check(key, n).is_ok()
+    }
+
+    /// Wait until `n` permits are available for `key` and take them
+    pub async fn acquire(&self, key: &K, n: u32) -> Result<(), FunctionError> {
+        let limiter = self.limiter(key);
+        acquire_from(limiter.as_ref(), n).await
+    }
+
+    /// Number of keys currently tracked
+    pub fn len(&self) -> usize {
+        self.state.lock().unwrap().entries.len()
+    }
+
+    /// Check whether no key is tracked
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Drop limiters that have been idle for `idle_timeout`
+    ///
+    /// Happens automatically during calls; returns the number of evicted keys.
+    pub fn evict_idle(&self) -> usize {
+        let mut state = self.state.lock().unwrap();
+        self.sweep(&mut state, Instant::now())
+    }
+
+    fn limiter(&self, key: &K) -> Arc<L> {
+        let now = Instant::now();
+        let mut state = self.state.lock().unwrap();
+        if now.duration_since(state.last_sweep) >= self.idle_timeout {
+            self.sweep(&mut state, now);
+        }
+
+        let entry = state.entries.entry(key.clone()).or_insert_with(|| KeyedEntry {
+            limiter: Arc::new((self.factory)()),
+            last_used: now,
+        });
+        entry.last_used = now;
+        entry.limiter.clone()
+    }
+
+    fn sweep(&self, state: &mut KeyedState<K, L>, now: Instant) -> usize {
+        let before = state.entries.len();
+        state.entries.retain(|_, entry| {
+            // Limiters still held by a waiting caller are in use
+            Arc::strong_count(&entry.limiter) > 1
+                || now.duration_since(entry.last_used) < self.idle_timeout
+        });
+        state.last_sweep = now;
+        before - state.entries.len()
+    }
+}
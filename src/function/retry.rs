@@ -0,0 +1,454 @@
+//! Retry policies with backoff strategies
+
+use super::{FunctionError, RetryOptions};
+use crate::error::{NetworkError, UtilsError};
+use rand::{Rng, rng};
+use std::error::Error;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::{Instant, sleep};
+
+/// How long to wait before each retry
+#[derive(Debug, Clone, PartialEq)]
+pub enum Backoff {
+    /// The same delay every time
+    Constant(Duration),
+    /// `initial + increment * (attempt - 1)`, capped at `max`
+    Linear {
+        initial: Duration,
+        increment: Duration,
+        max: Duration,
+    },
+    /// `initial * multiplier^(attempt - 1)`, capped at `max`
+    Exponential {
+        initial: Duration,
+        multiplier: f64,
+        max: Duration,
+    },
+    /// Random delay between zero and the exponential delay (`base * 2^(attempt - 1)`)
+    FullJitter { base: Duration, max: Duration },
+    /// Random delay between `base` and three times the previous delay
+    DecorrelatedJitter { base: Duration, max: Duration },
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Backoff::Constant(Duration::ZERO)
+    }
+}
+
+impl Backoff {
+    /// Exponential backoff doubling from `initial` up to `max`
+    pub fn exponential(initial: Duration, max: Duration) -> Self {
+        Backoff::Exponential {
+            initial,
+            multiplier: 2.0,
+            max,
+        }
+    }
+
+    /// Delay before retry number `attempt` (starting at 1)
+    ///
+    /// `previous` is the delay used before the previous retry, or zero for the
+    /// first one; only [`Backoff::DecorrelatedJitter`] depends on it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use mudssky_utils::function::Backoff;
+    /// use std::time::Duration;
+    ///
+    /// let backoff = Backoff::exponential(Duration::from_millis(100), Duration::from_secs(1));
+    /// assert_eq!(backoff.delay(1, Duration::ZERO), Duration::from_millis(100));
+    /// assert_eq!(backoff.delay(3, Duration::ZERO), Duration::from_millis(400));
+    /// assert_eq!(backoff.delay(10, Duration::ZERO), Duration::from_secs(1));
+    /// ```
+    pub fn delay(&self, attempt: usize, previous: Duration) -> Duration {
+        let step = attempt.saturating_sub(1);
+        match self {
+            Backoff::Constant(delay) => *delay,
+            Backoff::Linear {
+                initial,
+                increment,
+                max,
+            } => {
+                let step = u32::try_from(step).unwrap_or(u32::MAX);
+                initial.saturating_add(increment.saturating_mul(step)).min(*max)
+            }
+            Backoff::Exponential {
+                initial,
+                multiplier,
+                max,
+            } => capped(
+                initial.as_secs_f64() * multiplier.max(0.0).powi(exponent(step)),
+                *max,
+            ),
+            Backoff::FullJitter { base, max } => {
+                let ceiling = capped(base.as_secs_f64() * 2f64.powi(exponent(step)), *max);
+                random_between(Duration::ZERO, ceiling)
+            }
+            Backoff::DecorrelatedJitter { base, max } => {
+                let upper = capped(previous.max(*base).as_secs_f64() * 3.0, *max);
+                random_between((*base).min(*max), upper)
+            }
+        }
+    }
+}
+
+fn exponent(step: usize) -> i32 {
+    i32::try_from(step).unwrap_or(i32::MAX)
+}
+
+fn capped(secs: f64, max: Duration) -> Duration {
+    if secs.is_finite() && secs < max.as_secs_f64() {
+        Duration::from_secs_f64(secs.max(0.0))
+    } else {
+        max
+    }
+}
+
+fn random_between(low: Duration, high: Duration) -> Duration {
+    if high <= low {
+        return low;
+    }
+    let nanos = rng().random_range(low.as_nanos()..=high.as_nanos());
+    Duration::from_nanos(u64::try_from(nanos).unwrap_or(u64::MAX))
+}
+
+type RetryHook = dyn Fn(usize, &(dyn Error + 'static), Duration) + Send + Sync;
+type RetryPredicate = dyn Fn(&(dyn Error + 'static)) -> bool + Send + Sync;
+
+/// Why retrying stopped
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RetryStop {
+    /// Every allowed retry failed
+    Exhausted,
+    /// The next retry would have exceeded the time budget
+    BudgetExceeded,
+    /// The retry predicate rejected the error
+    NotRetryable,
+}
+
+/// Classify an error for [`RetryPolicy::retry_if`]
+///
+/// Looks for a [`NetworkError`] (directly or inside a [`UtilsError`]) in the
+/// `source()` chain: `5xx` and `429` statuses are retryable, other `4xx`
+/// statuses are not. Errors without a status code and all other errors are
+/// considered retryable.
+///
+/// # Examples
+///
+/// ```
+/// use mudssky_utils::error::NetworkError;
+/// use mudssky_utils::function::is_retryable_error;
+///
+/// assert!(is_retryable_error(&NetworkError::with_status("get", "unavailable", 503)));
+/// assert!(is_retryable_error(&NetworkError::with_status("get", "slow down", 429)));
+/// assert!(!is_retryable_error(&NetworkError::with_status("get", "not found", 404)));
+/// ```
+pub fn is_retryable_error(error: &(dyn Error + 'static)) -> bool {
+    let mut current = Some(error);
+    while let Some(err) = current {
+        let network =
+            err.downcast_ref::<NetworkError>().or(match err.downcast_ref::<UtilsError>() {
+                Some(UtilsError::Network(e)) => Some(e),
+                _ => None,
+            });
+        if let Some(network) = network {
+            return match network.status_code() {
+                Some(429) => true,
+                Some(status) => !(400..500).contains(&status),
+                None => true,
+            };
+        }
+        current = err.source();
+    }
+    true
+}
+
+/// Retry configuration with backoff, time budget and a retry hook
+///
+/// # Examples
+///
+/// ```
+/// use mudssky_utils::function::{Backoff, RetryPolicy, with_retry_policy};
+/// use std::time::Duration;
+///
+/// # #[tokio::main]
+/// # async fn main() {
+/// let policy = RetryPolicy::new(5)
+///     .with_backoff(Backoff::FullJitter {
+///         base: Duration::from_millis(1),
+///         max: Duration::from_millis(20),
+///     })
+///     .with_max_elapsed(Duration::from_secs(2))
+///     .on_retry(|attempt, error, delay| {
+///         eprintln!("retry #{attempt} in {delay:?}: {error}");
+///     });
+///
+/// let result = with_retry_policy(
+///     || async { Ok::<_, Box<dyn std::error::Error + Send + Sync>>(42) },
+///     &policy,
+/// )
+/// .await;
+/// assert_eq!(result.unwrap(), 42);
+/// # }
+/// ```
+#[derive(Clone, Default)]
+pub struct RetryPolicy {
+    /// Maximum number of retries after the first attempt
+    pub max_retries: usize,
+    /// Delay strategy between attempts
+    pub backoff: Backoff,
+    /// Give up when the next retry would start after this much time
+    pub max_elapsed: Option<Duration>,
+    on_retry: Option<Arc<RetryHook>>,
+    retry_if: Option<Arc<RetryPredicate>>,
+}
+
+impl std::fmt::Debug for RetryPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RetryPolicy")
+            .field("max_retries", &self.max_retries)
+            .field("backoff", &self.backoff)
+            .field("max_elapsed", &self.max_elapsed)
+            .field("on_retry", &self.on_retry.as_ref().map(|_| "<hook>"))
+            .field("retry_if", &self.retry_if.as_ref().map(|_| "<predicate>"))
+            .finish()
+    }
+}
+
+impl RetryPolicy {
+    /// Retry up to `max_retries` times without delay
+    pub fn new(max_retries: usize) -> Self {
+        Self {
+            max_retries,
+            ..Default::default()
+        }
+    }
+
+    /// Set the backoff strategy
+    pub fn with_backoff(mut self, backoff: Backoff) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Set the total time budget
+    pub fn with_max_elapsed(mut self, max_elapsed: Duration) -> Self {
+        self.max_elapsed = Some(max_elapsed);
+        self
+    }
+
+    /// Call `hook` before each retry with the attempt number (starting at 1),
+    /// the error that caused it and the delay before it
+    pub fn on_retry<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(usize, &(dyn Error + 'static), Duration) + Send + Sync + 'static,
+    {
+        self.on_retry = Some(Arc::new(hook));
+        self
+    }
+
+    /// Only retry errors for which `predicate` returns `true`
+    ///
+    /// See [`is_retryable_error`] for a built-in classifier.
+    pub fn retry_if<F>(mut self, predicate: F) -> Self
+    where
+        F: Fn(&(dyn Error + 'static)) -> bool + Send + Sync + 'static,
+    {
+        self.retry_if = Some(Arc::new(predicate));
+        self
+    }
+
+    /// Decide whether to retry after a failure
+    ///
+    /// Returns the delay before retry number `attempt`, or why retrying
+    /// stops. Calls the retry hook.
+    pub(crate) fn next_retry(
+        &self,
+        attempt: usize,
+        previous: Duration,
+        started: Instant,
+        error: &(dyn Error + 'static),
+    ) -> Result<Duration, RetryStop> {
+        if self.retry_if.as_ref().is_some_and(|retry_if| !retry_if(error)) {
+            return Err(RetryStop::NotRetryable);
+        }
+        if attempt > self.max_retries {
+            return Err(RetryStop::Exhausted);
+        }
+        let delay = self.backoff.delay(attempt, previous);
+        if self.max_elapsed.is_some_and(|budget| started.elapsed() + delay > budget) {
+            return Err(RetryStop::BudgetExceeded);
+        }
+        if let Some(hook) = &self.on_retry {
+            hook(attempt, error, delay);
+        }
+        Ok(delay)
+    }
+}
+
+impl From<RetryOptions> for RetryPolicy {
+    fn from(options: RetryOptions) -> Self {
+        RetryPolicy::new(options.max_retries).with_backoff(Backoff::Constant(options.delay))
+    }
+}
+
+/// All errors of a failed retry loop, in attempt order
+///
+/// Returned by [`with_retry_typed`]; the source is the last error.
+#[derive(Debug, Clone)]
+pub struct RetryError<E> {
+    errors: Vec<E>,
+    stop: RetryStop,
+}
+
+impl<E> RetryError<E> {
+    /// Why retrying stopped
+    pub fn stop(&self) -> RetryStop {
+        self.stop
+    }
+
+    /// Number of attempts made, including the first one
+    pub fn attempts(&self) -> usize {
+        self.errors.len()
+    }
+
+    /// Error of the final attempt
+    pub fn last(&self) -> &E {
+        self.errors.last().expect("a retry error holds at least one error")
+    }
+
+    /// Errors of every attempt, oldest first
+    pub fn errors(&self) -> &[E] {
+        &self.errors
+    }
+
+    /// Take the error of the final attempt
+    pub fn into_last(mut self) -> E {
+        self.errors.pop().expect("a retry error holds at least one error")
+    }
+
+    /// Take the errors of every attempt
+    pub fn into_errors(self) -> Vec<E> {
+        self.errors
+    }
+}
+
+impl<E: std::fmt::Display> std::fmt::Display for RetryError<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let reason = match self.stop {
+            RetryStop::Exhausted => "retries exhausted",
+            RetryStop::BudgetExceeded => "retry budget exceeded",
+            RetryStop::NotRetryable => "non-retryable error",
+        };
+        write!(
+            f,
+            "{reason} after {} attempts: {}",
+            self.attempts(),
+            self.last()
+        )
+    }
+}
+
+impl<E: Error + 'static> Error for RetryError<E> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(self.last())
+    }
+}
+
+/// Execute a function with a typed error, retrying according to a [`RetryPolicy`]
+///
+/// # Examples
+///
+/// ```
+/// use mudssky_utils::error::NetworkError;
+/// use mudssky_utils::function::{RetryPolicy, RetryStop, is_retryable_error, with_retry_typed};
+///
+/// # #[tokio::main]
+/// # async fn main() {
+/// let policy = RetryPolicy::new(3).retry_if(is_retryable_error);
+/// let result = with_retry_typed(
+///     || async { Err::<(), _>(NetworkError::with_status("get", "not found", 404)) },
+///     &policy,
+/// )
+/// .await;
+///
+/// let error = result.unwrap_err();
+/// assert_eq!(error.stop(), RetryStop::NotRetryable);
+/// assert_eq!(error.attempts(), 1);
+/// assert_eq!(error.last().status_code(), Some(404));
+/// # }
+/// ```
+pub async fn with_retry_typed<F, Fut, T, E>(
+    func: F,
+    policy: &RetryPolicy,
+) -> Result<T, RetryError<E>>
+where
+    F: Fn() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+    E: Error + 'static,
+{
+    retry_loop(func, policy, |e| e).await
+}
+
+/// Execute a function, retrying failures according to a [`RetryPolicy`]
+pub async fn with_retry_policy<F, Fut, T>(func: F, policy: &RetryPolicy) -> Result<T, FunctionError>
+where
+    F: Fn() -> Fut,
+    Fut: Future<Output = Result<T, Box<dyn Error + Send + Sync>>>,
+{
+    let error = match retry_loop(func, policy, |e| e.as_ref()).await {
+        Ok(result) => return Ok(result),
+        Err(error) => error,
+    };
+    let retries = error.attempts() - 1;
+    let last = error.last();
+    Err(FunctionError::RetryExhausted(match error.stop() {
+        RetryStop::Exhausted => {
+            format!("Function failed after {retries} retries. Last error: {last}")
+        }
+        RetryStop::BudgetExceeded => format!(
+            "Retry budget of {:?} exceeded after {retries} retries. Last error: {last}",
+            policy.max_elapsed.unwrap_or_default()
+        ),
+        RetryStop::NotRetryable => {
+            format!("Non-retryable error after {retries} retries. Last error: {last}")
+        }
+    }))
+}
+
+async fn retry_loop<F, Fut, T, E, D>(
+    func: F,
+    policy: &RetryPolicy,
+    as_error: D,
+) -> Result<T, RetryError<E>>
+where
+    F: Fn() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+    D: Fn(&E) -> &(dyn Error + 'static),
+{
+    let started = Instant::now();
+    let mut errors = Vec::new();
+    let mut delay = Duration::ZERO;
+
+    loop {
+        let error = match func().await {
+            Ok(result) => return Ok(result),
+            Err(error) => error,
+        };
+        let next = policy.next_retry(errors.len() + 1, delay, started, as_error(&error));
+        errors.push(error);
+
+        match next {
+            Ok(next) => {
+                delay = next;
+                if !delay.is_zero() {
+                    sleep(delay).await;
+                }
+            }
+            Err(stop) => return Err(RetryError { errors, stop }),
+        }
+    }
+}
@@ -0,0 +1,258 @@
+//! Throttled callbacks with trailing invocations and shared results
+
+use super::ThrottleOptions;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::watch;
+use tokio::time::{Instant, sleep_until};
+
+type Callback<A, T> = dyn Fn(A) -> Pin<Box<dyn Future<Output = T> + Send>> + Send + Sync;
+
+struct ThrottleState<A, T> {
+    /// Arguments of the latest call made during the current window
+    pending_args: Option<A>,
+    /// Notifies the callers waiting for the next trailing invocation
+    waiters: watch::Sender<Option<T>>,
+    last_result: Option<T>,
+    /// End of the current window, while a window is open
+    window_end: Option<Instant>,
+    /// Bumped whenever the window is closed early, so its timer task exits
+    generation: u64,
+    invocations: u64,
+}
+
+struct Shared<A, T> {
+    callback: Box<Callback<A, T>>,
+    wait: Duration,
+    options: ThrottleOptions,
+    state: Mutex<ThrottleState<A, T>>,
+}
+
+/// What a call has to do once the state lock is released
+enum Action<A, T> {
+    Invoke(A),
+    Wait(watch::Receiver<Option<T>>),
+    Done(Option<T>),
+}
+
+/// A callback that runs at most once per `wait` window
+///
+/// With `leading` the first call of a window runs immediately; with `trailing`
+/// calls made during a window are collapsed into one invocation with the latest
+/// arguments when the window ends. Callers whose call was throttled wait for
+/// and share the result of that trailing invocation. Clones share the same
+/// state.
+///
+/// Must be used inside a Tokio runtime.
+///
+/// # Examples
+///
+/// ```
+/// use mudssky_utils::function::{ThrottleOptions, Throttled};
+/// use std::time::Duration;
+///
+/// # #[tokio::main]
+/// # async fn main() {
+/// let options = ThrottleOptions { leading: true, trailing: true };
+/// let fetch = Throttled::new(Duration::from_millis(20), options, |page: u32| async move {
+///     format!("page {page}")
+/// });
+///
+/// assert_eq!(fetch.call(1).await.as_deref(), Some("page 1"));
+/// let (a, b) = tokio::join!(fetch.call(2), fetch.call(3));
+/// // Both throttled callers share the trailing call made with the latest arguments
+/// assert_eq!(a.as_deref(), Some("page 3"));
+/// assert_eq!(b.as_deref(), Some("page 3"));
+/// # }
+/// ```
+pub struct Throttled<A, T> {
+    shared: Arc<Shared<A, T>>,
+}
+
+impl<A, T> Clone for Throttled<A, T> {
+    fn clone(&self) -> Self {
+        Self {
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl<A, T> std::fmt::Debug for Throttled<A, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Throttled")
+            .field("wait", &self.shared.wait)
+            .field("options", &self.shared.options)
+            .finish()
+    }
+}
+
+impl<A, T> Throttled<A, T>
+where
+    A: Send + 'static,
+    T: Clone + Send + Sync + 'static,
+{
+    /// Wrap an async callback
+    pub fn new<F, Fut>(wait: Duration, options: ThrottleOptions, callback: F) -> Self
+    where
+        F: Fn(A) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = T> + Send + 'static,
+    {
+        Self {
+            shared: Arc::new(Shared {
+                callback: Box::new(move |args| Box::pin(callback(args))),
+                wait,
+                options,
+                state: Mutex::new(ThrottleState {
+                    pending_args: None,
+                    waiters: watch::channel(None).0,
+                    last_result: None,
+                    window_end: None,
+                    generation: 0,
+                    invocations: 0,
+                }),
+            }),
+        }
+    }
+
+    /// Call the throttled function
+    ///
+    /// Resolves to the result of the invocation that covers this call: the
+    /// call itself on the leading edge, or the trailing invocation of the
+    /// window otherwise. Without `trailing`, throttled calls resolve to the
+    /// latest result right away. Returns `None` when no invocation covers the
+    /// call, e.g. after [`Throttled::cancel`].
+    pub async fn call(&self, args: A) -> Option<T> {
+        let action = {
+            let mut state = self.shared.state.lock().unwrap();
+            let now = Instant::now();
+            let options = &self.shared.options;
+
+            if state.window_end.is_none() {
+                if options.leading {
+                    self.open_window(&mut state, now);
+                    state.invocations += 1;
+                    Action::Invoke(args)
+                } else if options.trailing {
+                    self.open_window(&mut state, now);
+                    state.pending_args = Some(args);
+                    Action::Wait(state.waiters.subscribe())
+                } else {
+                    Action::Done(None)
+                }
+            } else if options.trailing {
+                state.pending_args = Some(args);
+                Action::Wait(state.waiters.subscribe())
+            } else {
+                Action::Done(state.last_result.clone())
+            }
+        };
+
+        match action {
+            Action::Invoke(args) => {
+                let result = (self.shared.callback)(args).await;
+                self.shared.state.lock().unwrap().last_result = Some(result.clone());
+                Some(result)
+            }
+            Action::Wait(mut receiver) => {
+                receiver.changed().await.ok()?;
+                receiver.borrow().clone()
+            }
+            Action::Done(result) => result,
+        }
+    }
+
+    /// Run the pending trailing call right away
+    ///
+    /// Starts a new window and returns the result, or `None` when no call was
+    /// pending.
+    pub async fn flush(&self) -> Option<T> {
+        let (args, waiters) = {
+            let mut state = self.shared.state.lock().unwrap();
+            let args = state.pending_args.take()?;
+            state.window_end = Some(Instant::now() + self.shared.wait);
+            state.invocations += 1;
+            (
+                args,
+                std::mem::replace(&mut state.waiters, watch::channel(None).0),
+            )
+        };
+        Some(self.invoke_trailing(args, waiters).await)
+    }
+
+    /// Drop the pending trailing call and close the current window
+    ///
+    /// Callers waiting for the trailing call resolve to `None`.
+    pub fn cancel(&self) {
+        let mut state = self.shared.state.lock().unwrap();
+        state.generation = state.generation.wrapping_add(1);
+        state.window_end = None;
+        state.pending_args = None;
+        state.waiters = watch::channel(None).0;
+    }
+
+    /// Check whether a trailing call is waiting for the window to end
+    pub fn is_pending(&self) -> bool {
+        self.shared.state.lock().unwrap().pending_args.is_some()
+    }
+
+    /// Number of times the callback has been invoked
+    pub fn invocation_count(&self) -> u64 {
+        self.shared.state.lock().unwrap().invocations
+    }
+}
+
+impl<A, T> Throttled<A, T>
+where
+    A: Send + 'static,
+    T: Clone + Send + Sync + 'static,
+{
+    fn open_window(&self, state: &mut ThrottleState<A, T>, now: Instant) {
+        state.window_end = Some(now + self.shared.wait);
+        let generation = state.generation;
+        let this = self.clone();
+        tokio::spawn(async move { this.run_timer(generation).await });
+    }
+
+    /// Close windows as they end, running trailing calls in between
+    async fn run_timer(self, generation: u64) {
+        loop {
+            let next = {
+                let mut state = self.shared.state.lock().unwrap();
+                if state.generation != generation {
+                    return;
+                }
+                let Some(window_end) = state.window_end else {
+                    return;
+                };
+                let now = Instant::now();
+                if now < window_end {
+                    Err(window_end)
+                } else if let Some(args) = state.pending_args.take() {
+                    state.window_end = Some(now + self.shared.wait);
+                    state.invocations += 1;
+                    let waiters = std::mem::replace(&mut state.waiters, watch::channel(None).0);
+                    Ok((args, waiters))
+                } else {
+                    state.window_end = None;
+                    return;
+                }
+            };
+            match next {
+                Ok((args, waiters)) => {
+                    self.invoke_trailing(args, waiters).await;
+                }
+                Err(window_end) => sleep_until(window_end).await,
+            }
+        }
+    }
+
+    async fn invoke_trailing(&self, args: A, waiters: watch::Sender<Option<T>>) -> T {
+        let result = (self.shared.callback)(args).await;
+        self.shared.state.lock().unwrap().last_result = Some(result.clone());
+        // Nobody may be waiting any more, which is fine
+        let _ = waiters.send(Some(result.clone()));
+        result
+    }
+}
@@ -8,8 +8,59 @@ use once_cell::sync::Lazy;
 use serde_json::{Value, json};
 use std::collections::HashMap;
 use std::fmt;
+use std::sync::atomic::{AtomicU8, Ordering};
 use std::sync::{Arc, Mutex};
 
+mod async_output;
+mod color;
+mod config;
+mod context;
+mod error_log;
+mod file;
+mod filter;
+mod log_bridge;
+mod logfmt;
+mod macros;
+mod memory;
+mod multi;
+mod pattern;
+mod query;
+mod reader;
+mod redact;
+mod sampling;
+mod socket;
+mod span;
+mod syslog;
+
+pub use async_output::{AsyncOutput, AsyncOutputGuard, AsyncOutputOptions, OverflowPolicy};
+pub use color::{ColorMode, ColoredFormatter, SplitConsoleOutput, level_color, strip_ansi};
+pub use config::{
+    FormatterSpec, LOG_ENV_PREFIX, LoggerSpec, LoggingConfig, OutputKind, OutputSpec,
+};
+pub use context::{
+    ContextGuard, current_context, push_context, with_task_context, with_thread_context,
+};
+pub use error_log::error_metadata;
+pub use file::{FileOutput, FileOutputOptions};
+pub use filter::{
+    DEFAULT_FILTER_ENV, FilterDirective, LogFilter, active_filter, apply_filter_from_env,
+    clear_filter, set_filter, set_filter_spec,
+};
+pub use log_bridge::{LogBridge, init_log_bridge, set_log_bridge_fallback};
+pub use logfmt::LogfmtFormatter;
+#[doc(hidden)]
+pub use macros::__field_value;
+pub use memory::MemoryOutput;
+pub use multi::{MultiOutput, OutputSink};
+pub use pattern::PatternFormatter;
+pub use query::LogQuery;
+pub use reader::{LogReader, LogTail, parse_json_line, read_json_log};
+pub use redact::{DEFAULT_REDACTION_MASK, Detector, Redactor};
+pub use sampling::{LogSampler, SampleDecision, SampleKey, SamplingPolicy};
+pub use socket::{Framing, SocketOutput, SocketOutputOptions, SocketTarget};
+pub use span::{Span, SpanOptions};
+pub use syslog::{SyslogFacility, SyslogFormatter, syslog_severity};
+
 /// Log levels in order of severity
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub enum LogLevel {
@@ -32,6 +83,18 @@ impl fmt::Display for LogLevel {
     }
 }
 
+impl LogLevel {
+    fn from_u8(value: u8) -> Self {
+        match value {
+            0 => LogLevel::Trace,
+            1 => LogLevel::Debug,
+            2 => LogLevel::Info,
+            3 => LogLevel::Warn,
+            _ => LogLevel::Error,
+        }
+    }
+}
+
 impl std::str::FromStr for LogLevel {
     type Err = String;
 
@@ -160,6 +223,14 @@ impl LogFormatter for JsonFormatter {
 /// Log output trait
 pub trait LogOutput: Send + Sync {
     fn write(&self, formatted_message: &str);
+
+    /// Write a formatted entry; outputs that need the raw entry override this
+    fn write_entry(&self, _entry: &LogEntry, formatted_message: &str) {
+        self.write(formatted_message);
+    }
+
+    /// Flush any buffered messages
+    fn flush(&self) {}
 }
 
 /// Console output
@@ -170,6 +241,11 @@ impl LogOutput for ConsoleOutput {
     fn write(&self, formatted_message: &str) {
         println!("{formatted_message}");
     }
+
+    fn flush(&self) {
+        use std::io::Write;
+        let _ = std::io::stdout().flush();
+    }
 }
 
 /// Logger configuration
@@ -179,6 +255,10 @@ pub struct LoggerConfig {
     pub name: String,
     pub formatter: Arc<dyn LogFormatter>,
     pub output: Arc<dyn LogOutput>,
+    /// Sampler shared by all clones of the logger
+    pub sampler: Option<Arc<LogSampler>>,
+    /// Redaction applied to every entry before formatting
+    pub redactor: Option<Arc<Redactor>>,
 }
 
 impl std::fmt::Debug for LoggerConfig {
@@ -188,6 +268,8 @@ impl std::fmt::Debug for LoggerConfig {
             .field("name", &self.name)
             .field("formatter", &"<formatter>")
             .field("output", &"<output>")
+            .field("sampler", &self.sampler)
+            .field("redactor", &self.redactor)
             .finish()
     }
 }
@@ -200,6 +282,8 @@ impl LoggerConfig {
             level: LogLevel::Info,
             formatter: Arc::new(SimpleFormatter::default()),
             output: Arc::new(ConsoleOutput),
+            sampler: None,
+            redactor: None,
         }
     }
 
@@ -220,18 +304,35 @@ impl LoggerConfig {
         self.output = output;
         self
     }
+
+    /// Sample or rate-limit entries with the given policy
+    pub fn with_sampling(mut self, policy: SamplingPolicy) -> Self {
+        self.sampler = Some(Arc::new(LogSampler::new(policy)));
+        self
+    }
+
+    /// Mask sensitive data before entries are formatted
+    pub fn with_redactor(mut self, redactor: Redactor) -> Self {
+        self.redactor = Some(Arc::new(redactor));
+        self
+    }
 }
 
 /// Logger implementation
+///
+/// Clones share the same level, so changing it through the registry also
+/// affects loggers previously handed out by [`get_logger`].
 #[derive(Debug, Clone)]
 pub struct Logger {
     config: LoggerConfig,
+    level: Arc<AtomicU8>,
 }
 
 impl Logger {
     /// Create a new logger
     pub fn new(config: LoggerConfig) -> Self {
-        Self { config }
+        let level = Arc::new(AtomicU8::new(config.level as u8));
+        Self { config, level }
     }
 
     /// Create a logger with default configuration
@@ -239,17 +340,75 @@ impl Logger {
         Self::new(LoggerConfig::new(name.to_string()))
     }
 
+    /// Get the logger name
+    pub fn name(&self) -> &str {
+        &self.config.name
+    }
+
+    /// Get the current log level
+    pub fn level(&self) -> LogLevel {
+        LogLevel::from_u8(self.level.load(Ordering::Relaxed))
+    }
+
+    /// Change the log level of this logger and all its clones
+    pub fn set_level(&self, level: LogLevel) {
+        self.level.store(level as u8, Ordering::Relaxed);
+    }
+
     /// Check if a log level is enabled
     pub fn is_enabled(&self, level: LogLevel) -> bool {
-        level >= self.config.level
+        level >= self.level()
+    }
+
+    /// Log a prepared entry if its level is enabled
+    ///
+    /// The entry keeps its own `logger_name`, which lets bridges and child
+    /// targets write through this logger's formatter and output. Fields from
+    /// the current log context are merged into its metadata.
+    pub fn log_entry(&self, entry: LogEntry) {
+        if self.is_enabled(entry.level) {
+            self.dispatch(entry);
+        }
+    }
+
+    /// Sample and write an entry whose level was already checked by the caller
+    fn dispatch(&self, entry: LogEntry) {
+        if let Some(sampler) = &self.config.sampler {
+            let decision = sampler.check(&entry);
+            for summary in decision.summaries {
+                self.write(summary);
+            }
+            if !decision.allow {
+                return;
+            }
+        }
+        self.write(entry);
+    }
+
+    fn write(&self, mut entry: LogEntry) {
+        context::merge_current_context(&mut entry.metadata);
+        if let Some(redactor) = &self.config.redactor {
+            redactor.redact(&mut entry);
+        }
+        let formatted = self.config.formatter.format(&entry);
+        self.config.output.write_entry(&entry, &formatted);
+    }
+
+    /// Flush the logger output, writing pending sampling summaries first
+    pub fn flush(&self) {
+        if let Some(sampler) = &self.config.sampler {
+            for summary in sampler.drain_summaries() {
+                self.write(summary);
+            }
+        }
+        self.config.output.flush();
     }
 
     /// Log a message at the specified level
     pub fn log(&self, level: LogLevel, message: &str) {
         if self.is_enabled(level) {
             let entry = LogEntry::new(level, self.config.name.clone(), message.to_string());
-            let formatted = self.config.formatter.format(&entry);
-            self.config.output.write(&formatted);
+            self.log_entry(entry);
         }
     }
 
@@ -263,8 +422,7 @@ impl Logger {
         if self.is_enabled(level) {
             let entry = LogEntry::new(level, self.config.name.clone(), message.to_string())
                 .with_metadata_map(metadata);
-            let formatted = self.config.formatter.format(&entry);
-            self.config.output.write(&formatted);
+            self.log_entry(entry);
         }
     }
 
@@ -306,23 +464,52 @@ pub fn get_logger(name: &str) -> Logger {
         logger.clone()
     } else {
         let logger = Logger::with_name(name);
+        if let Some(level) = filter::active_level_for(name) {
+            logger.set_level(level);
+        }
         registry.insert(name.to_string(), logger.clone());
         logger
     }
 }
 
+/// Get the logger registered for `name` or its nearest registered ancestor
+///
+/// `app::db::pool` falls back to `app::db`, then `app`. Unlike [`get_logger`]
+/// nothing is registered when no logger matches.
+fn resolve_logger(name: &str) -> Option<Logger> {
+    let registry = LOGGER_REGISTRY.lock().unwrap();
+    let mut current = name;
+    loop {
+        if let Some(logger) = registry.get(current) {
+            return Some(logger.clone());
+        }
+        current = &current[..current.rfind("::")?];
+    }
+}
+
 /// Create a logger with custom configuration
 pub fn create_logger(config: LoggerConfig) -> Logger {
     let logger = Logger::new(config.clone());
+    if let Some(level) = filter::active_level_for(&config.name) {
+        logger.set_level(level);
+    }
     let mut registry = LOGGER_REGISTRY.lock().unwrap();
     registry.insert(config.name.clone(), logger.clone());
     logger
 }
 
+/// Names of all registered loggers, sorted
+pub fn logger_names() -> Vec<String> {
+    let registry = LOGGER_REGISTRY.lock().unwrap();
+    let mut names: Vec<String> = registry.keys().cloned().collect();
+    names.sort();
+    names
+}
+
 /// Set the global log level for all loggers
 pub fn set_global_level(level: LogLevel) {
-    let mut registry = LOGGER_REGISTRY.lock().unwrap();
-    for logger in registry.values_mut() {
-        logger.config.level = level;
+    let registry = LOGGER_REGISTRY.lock().unwrap();
+    for logger in registry.values() {
+        logger.set_level(level);
     }
 }
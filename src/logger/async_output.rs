@@ -0,0 +1,283 @@
+//! Non-blocking output backed by a dedicated writer thread
+
+use super::{LogEntry, LogOutput};
+use std::collections::VecDeque;
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::{self, JoinHandle};
+
+/// What to do when the queue of an [`AsyncOutput`] is full
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OverflowPolicy {
+    /// Block the caller until the worker frees a slot
+    #[default]
+    Block,
+    /// Discard the message being written
+    DropNewest,
+    /// Discard the oldest queued message to make room
+    DropOldest,
+}
+
+/// Async output options
+#[derive(Debug, Clone)]
+pub struct AsyncOutputOptions {
+    /// Maximum number of queued messages
+    pub capacity: usize,
+    /// Behaviour when the queue is full
+    pub overflow: OverflowPolicy,
+}
+
+impl Default for AsyncOutputOptions {
+    fn default() -> Self {
+        Self {
+            capacity: 1024,
+            overflow: OverflowPolicy::Block,
+        }
+    }
+}
+
+/// A queued line, with its entry when the caller provided one
+#[derive(Debug)]
+struct QueuedLine {
+    entry: Option<LogEntry>,
+    line: String,
+}
+
+impl QueuedLine {
+    fn write_to(&self, output: &dyn LogOutput) {
+        match &self.entry {
+            Some(entry) => output.write_entry(entry, &self.line),
+            None => output.write(&self.line),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct QueueState {
+    queue: VecDeque<QueuedLine>,
+    /// Messages dropped since the last dropped-count record
+    pending_dropped: u64,
+    total_dropped: u64,
+    busy: bool,
+    shutdown: bool,
+}
+
+struct Shared {
+    state: Mutex<QueueState>,
+    /// Signalled when messages are queued or shutdown is requested
+    not_empty: Condvar,
+    /// Signalled when the worker has taken messages off the queue
+    drained: Condvar,
+    inner: Arc<dyn LogOutput>,
+}
+
+/// Output that hands formatted lines to a background thread
+///
+/// `write` only pushes into a bounded queue; the wrapped output is called from a
+/// dedicated worker thread. Call [`AsyncOutput::flush`] or hold an
+/// [`AsyncOutputGuard`] to make sure queued lines are written before exit.
+pub struct AsyncOutput {
+    shared: Arc<Shared>,
+    capacity: usize,
+    overflow: OverflowPolicy,
+    worker: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl std::fmt::Debug for AsyncOutput {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AsyncOutput")
+            .field("capacity", &self.capacity)
+            .field("overflow", &self.overflow)
+            .field("inner", &"<output>")
+            .finish()
+    }
+}
+
+impl AsyncOutput {
+    /// Wrap an output with default options
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use mudssky_utils::logger::{AsyncOutput, ConsoleOutput, LoggerConfig};
+    /// use std::sync::Arc;
+    ///
+    /// let output = Arc::new(AsyncOutput::new(Arc::new(ConsoleOutput)));
+    /// let _guard = output.shutdown_guard();
+    /// let config = LoggerConfig::new("app".to_string()).with_output(output.clone());
+    /// ```
+    pub fn new(inner: Arc<dyn LogOutput>) -> Self {
+        Self::with_options(inner, AsyncOutputOptions::default())
+    }
+
+    /// Wrap an output with custom options
+    pub fn with_options(inner: Arc<dyn LogOutput>, options: AsyncOutputOptions) -> Self {
+        let shared = Arc::new(Shared {
+            state: Mutex::new(QueueState::default()),
+            not_empty: Condvar::new(),
+            drained: Condvar::new(),
+            inner,
+        });
+
+        let worker_shared = shared.clone();
+        let worker = thread::Builder::new()
+            .name("mudssky-log-writer".to_string())
+            .spawn(move || run_worker(worker_shared))
+            .expect("failed to spawn log writer thread");
+
+        Self {
+            shared,
+            capacity: options.capacity.max(1),
+            overflow: options.overflow,
+            worker: Mutex::new(Some(worker)),
+        }
+    }
+
+    /// Total number of messages dropped because the queue was full
+    pub fn dropped_count(&self) -> u64 {
+        self.shared.state.lock().unwrap().total_dropped
+    }
+
+    /// Number of messages waiting to be written
+    pub fn pending(&self) -> usize {
+        self.shared.state.lock().unwrap().queue.len()
+    }
+
+    /// Stop the worker after writing everything still queued
+    ///
+    /// Messages written after shutdown go straight to the wrapped output.
+    pub fn shutdown(&self) {
+        self.shared.state.lock().unwrap().shutdown = true;
+        self.shared.not_empty.notify_all();
+
+        if let Some(worker) = self.worker.lock().unwrap().take() {
+            let _ = worker.join();
+        }
+        self.shared.inner.flush();
+    }
+
+    /// Create a guard that shuts the output down when dropped
+    pub fn shutdown_guard(self: &Arc<Self>) -> AsyncOutputGuard {
+        AsyncOutputGuard {
+            output: self.clone(),
+        }
+    }
+}
+
+impl AsyncOutput {
+    fn enqueue(&self, item: QueuedLine) {
+        let mut state = self.shared.state.lock().unwrap();
+
+        if state.shutdown {
+            drop(state);
+            item.write_to(self.shared.inner.as_ref());
+            return;
+        }
+
+        if state.queue.len() >= self.capacity {
+            match self.overflow {
+                OverflowPolicy::Block => {
+                    while state.queue.len() >= self.capacity && !state.shutdown {
+                        state = self.shared.drained.wait(state).unwrap();
+                    }
+                    if state.shutdown {
+                        drop(state);
+                        item.write_to(self.shared.inner.as_ref());
+                        return;
+                    }
+                }
+                OverflowPolicy::DropNewest => {
+                    state.pending_dropped += 1;
+                    state.total_dropped += 1;
+                    return;
+                }
+                OverflowPolicy::DropOldest => {
+                    state.queue.pop_front();
+                    state.pending_dropped += 1;
+                    state.total_dropped += 1;
+                }
+            }
+        }
+
+        state.queue.push_back(item);
+        drop(state);
+        self.shared.not_empty.notify_one();
+    }
+}
+
+impl LogOutput for AsyncOutput {
+    fn write(&self, formatted_message: &str) {
+        self.enqueue(QueuedLine {
+            entry: None,
+            line: formatted_message.to_string(),
+        });
+    }
+
+    fn write_entry(&self, entry: &LogEntry, formatted_message: &str) {
+        self.enqueue(QueuedLine {
+            entry: Some(entry.clone()),
+            line: formatted_message.to_string(),
+        });
+    }
+
+    /// Block until every queued message has been written
+    fn flush(&self) {
+        let mut state = self.shared.state.lock().unwrap();
+        while (!state.queue.is_empty() || state.busy) && !state.shutdown {
+            state = self.shared.drained.wait(state).unwrap();
+        }
+        drop(state);
+        self.shared.inner.flush();
+    }
+}
+
+impl Drop for AsyncOutput {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+/// Flushes and stops an [`AsyncOutput`] when dropped
+///
+/// Keep it alive in `main` so queued lines are written at process exit.
+#[derive(Debug)]
+pub struct AsyncOutputGuard {
+    output: Arc<AsyncOutput>,
+}
+
+impl Drop for AsyncOutputGuard {
+    fn drop(&mut self) {
+        self.output.shutdown();
+    }
+}
+
+fn run_worker(shared: Arc<Shared>) {
+    loop {
+        let (batch, dropped) = {
+            let mut state = shared.state.lock().unwrap();
+            while state.queue.is_empty() && state.pending_dropped == 0 && !state.shutdown {
+                state = shared.not_empty.wait(state).unwrap();
+            }
+            if state.queue.is_empty() && state.pending_dropped == 0 && state.shutdown {
+                shared.drained.notify_all();
+                return;
+            }
+            state.busy = true;
+            let batch: Vec<QueuedLine> = state.queue.drain(..).collect();
+            let dropped = std::mem::take(&mut state.pending_dropped);
+            (batch, dropped)
+        };
+        shared.drained.notify_all();
+
+        if dropped > 0 {
+            shared.inner.write(&format!(
+                "[AsyncOutput] dropped {dropped} log messages (queue full)"
+            ));
+        }
+        for item in &batch {
+            item.write_to(shared.inner.as_ref());
+        }
+
+        shared.state.lock().unwrap().busy = false;
+        shared.drained.notify_all();
+    }
+}
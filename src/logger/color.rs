@@ -0,0 +1,241 @@
+//! ANSI colored console logging
+
+use super::{LogEntry, LogFormatter, LogLevel, LogOutput};
+use crate::env;
+
+const RESET: &str = "\x1b[0m";
+const DIM: &str = "\x1b[2m";
+
+/// When to emit ANSI colors
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorMode {
+    /// Color only when stdout is a terminal and `NO_COLOR` is not set
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorMode {
+    /// Resolve the mode to a yes/no decision for stdout
+    pub fn enabled(self) -> bool {
+        self.resolve(env::supports_color)
+    }
+
+    /// Resolve the mode to a yes/no decision for stderr
+    pub fn enabled_for_stderr(self) -> bool {
+        self.resolve(env::supports_color_stderr)
+    }
+
+    fn resolve(self, detect: fn() -> bool) -> bool {
+        match self {
+            ColorMode::Auto => detect(),
+            ColorMode::Always => true,
+            ColorMode::Never => false,
+        }
+    }
+}
+
+/// Remove ANSI escape sequences from `text`
+pub fn strip_ansi(text: &str) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\x1b' {
+            output.push(c);
+            continue;
+        }
+        // CSI sequences end with a byte in the `@`..=`~` range
+        if chars.next() == Some('[') {
+            for c in chars.by_ref() {
+                if ('@'..='~').contains(&c) {
+                    break;
+                }
+            }
+        }
+    }
+    output
+}
+
+/// ANSI color sequence used for a level
+pub fn level_color(level: LogLevel) -> &'static str {
+    match level {
+        LogLevel::Trace => "\x1b[35m",
+        LogLevel::Debug => "\x1b[34m",
+        LogLevel::Info => "\x1b[32m",
+        LogLevel::Warn => "\x1b[33m",
+        LogLevel::Error => "\x1b[1;31m",
+    }
+}
+
+/// Text formatter with colored levels and dimmed timestamps
+///
+/// Produces the same layout as [`SimpleFormatter`](super::SimpleFormatter).
+/// With [`ColorMode::Auto`] colors are decided once, at construction, by
+/// looking at stdout. When writing to a [`SplitConsoleOutput`], use
+/// [`ColorMode::Always`] and let the output drop colors per stream.
+#[derive(Debug, Clone)]
+pub struct ColoredFormatter {
+    pub include_timestamp: bool,
+    pub include_level: bool,
+    pub include_logger_name: bool,
+    colors: bool,
+}
+
+impl Default for ColoredFormatter {
+    fn default() -> Self {
+        Self::new(ColorMode::Auto)
+    }
+}
+
+impl ColoredFormatter {
+    /// Create a colored formatter
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use mudssky_utils::logger::{
+    ///     ColorMode, ColoredFormatter, LoggerConfig, LogLevel, SplitConsoleOutput,
+    /// };
+    /// use std::sync::Arc;
+    ///
+    /// let config = LoggerConfig::new("app".to_string())
+    ///     .with_formatter(Arc::new(ColoredFormatter::new(ColorMode::Always)))
+    ///     .with_output(Arc::new(SplitConsoleOutput::new(LogLevel::Warn)));
+    /// ```
+    pub fn new(mode: ColorMode) -> Self {
+        Self {
+            include_timestamp: true,
+            include_level: true,
+            include_logger_name: true,
+            colors: mode.enabled(),
+        }
+    }
+
+    /// Check whether this formatter emits colors
+    pub fn colors_enabled(&self) -> bool {
+        self.colors
+    }
+
+    fn paint(&self, color: &str, text: &str) -> String {
+        if self.colors {
+            format!("{color}{text}{RESET}")
+        } else {
+            text.to_string()
+        }
+    }
+}
+
+impl LogFormatter for ColoredFormatter {
+    fn format(&self, entry: &LogEntry) -> String {
+        let mut parts = Vec::new();
+
+        if self.include_timestamp {
+            let timestamp = entry.timestamp.format("%Y-%m-%d %H:%M:%S%.3f").to_string();
+            parts.push(self.paint(DIM, &timestamp));
+        }
+
+        if self.include_level {
+            let level = format!("[{}]", entry.level);
+            parts.push(self.paint(level_color(entry.level), &level));
+        }
+
+        if self.include_logger_name {
+            parts.push(format!("({})", entry.logger_name));
+        }
+
+        parts.push(entry.message.clone());
+
+        if !entry.metadata.is_empty() {
+            let metadata_str = serde_json::to_string(&entry.metadata).unwrap_or_default();
+            parts.push(self.paint(DIM, &format!("metadata: {metadata_str}")));
+        }
+
+        parts.join(" ")
+    }
+}
+
+/// Console output that sends severe entries to stderr
+///
+/// Entries at or above `stderr_level` are written to stderr, everything else
+/// to stdout. Plain [`write`](LogOutput::write) calls go to stdout.
+///
+/// Colors are decided per stream: ANSI sequences are stripped from messages
+/// written to a stream that should not receive them, so `app 2>err.log` keeps
+/// the file plain while the terminal stays colored.
+#[derive(Debug, Clone)]
+pub struct SplitConsoleOutput {
+    pub stderr_level: LogLevel,
+    stdout_colors: bool,
+    stderr_colors: bool,
+}
+
+impl Default for SplitConsoleOutput {
+    fn default() -> Self {
+        Self::new(LogLevel::Warn)
+    }
+}
+
+impl SplitConsoleOutput {
+    /// Create a split console output
+    ///
+    /// Colors are detected for each stream as with [`ColorMode::Auto`].
+    pub fn new(stderr_level: LogLevel) -> Self {
+        Self {
+            stderr_level,
+            stdout_colors: false,
+            stderr_colors: false,
+        }
+        .with_color(ColorMode::Auto)
+    }
+
+    /// Decide colors for both streams with `mode`
+    pub fn with_color(mut self, mode: ColorMode) -> Self {
+        self.stdout_colors = mode.enabled();
+        self.stderr_colors = mode.enabled_for_stderr();
+        self
+    }
+
+    /// Check whether entries of `level` go to stderr
+    pub fn uses_stderr(&self, level: LogLevel) -> bool {
+        level >= self.stderr_level
+    }
+
+    /// Check whether colors are kept for entries of `level`
+    pub fn colors_enabled(&self, level: LogLevel) -> bool {
+        if self.uses_stderr(level) {
+            self.stderr_colors
+        } else {
+            self.stdout_colors
+        }
+    }
+
+    fn prepare<'a>(&self, colors: bool, message: &'a str) -> std::borrow::Cow<'a, str> {
+        if colors || !message.contains('\x1b') {
+            message.into()
+        } else {
+            strip_ansi(message).into()
+        }
+    }
+}
+
+impl LogOutput for SplitConsoleOutput {
+    fn write(&self, formatted_message: &str) {
+        println!("{}", self.prepare(self.stdout_colors, formatted_message));
+    }
+
+    fn write_entry(&self, entry: &LogEntry, formatted_message: &str) {
+        let message = self.prepare(self.colors_enabled(entry.level), formatted_message);
+        if self.uses_stderr(entry.level) {
+            eprintln!("{message}");
+        } else {
+            println!("{message}");
+        }
+    }
+
+    fn flush(&self) {
+        use std::io::Write;
+        let _ = std::io::stdout().flush();
+        let _ = std::io::stderr().flush();
+    }
+}
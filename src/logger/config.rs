@@ -0,0 +1,498 @@
+//! Declarative logger configuration from JSON or environment variables
+
+use super::{
+    ColorMode, ColoredFormatter, ConsoleOutput, FileOutput, FileOutputOptions, JsonFormatter,
+    LogFilter, LogFormatter, LogLevel, LogOutput, LogfmtFormatter, Logger, LoggerConfig,
+    MultiOutput, PatternFormatter, Redactor, SimpleFormatter, SplitConsoleOutput, SyslogFormatter,
+    create_logger, set_filter,
+};
+use crate::error::ConfigError;
+use serde::{Deserialize, Deserializer};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Prefix of the environment variables read by [`LoggingConfig::from_env`]
+pub const LOG_ENV_PREFIX: &str = "MUDSSKY_LOG_";
+
+/// Formatter description
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Default)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum FormatterSpec {
+    #[default]
+    Simple,
+    Json,
+    Logfmt,
+    Colored {
+        /// `auto`, `always` or `never`
+        #[serde(default)]
+        color: Option<String>,
+    },
+    Pattern {
+        pattern: String,
+    },
+    Syslog {
+        app_name: String,
+        #[serde(default)]
+        hostname: Option<String>,
+    },
+}
+
+/// Output description
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum OutputKind {
+    Console,
+    SplitConsole {
+        #[serde(default)]
+        stderr_level: Option<String>,
+    },
+    File {
+        path: String,
+        /// Omitted uses the default size, `null` disables size rotation
+        #[serde(default, deserialize_with = "explicit_null")]
+        max_size: Option<Option<u64>>,
+        #[serde(default)]
+        rotate_daily: Option<bool>,
+        #[serde(default)]
+        max_files: Option<usize>,
+        #[serde(default)]
+        archive_pattern: Option<String>,
+    },
+}
+
+/// Output with an optional level threshold and formatter of its own
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct OutputSpec {
+    #[serde(flatten)]
+    pub kind: OutputKind,
+    #[serde(default)]
+    pub level: Option<String>,
+    #[serde(default)]
+    pub formatter: Option<FormatterSpec>,
+}
+
+/// Description of a single logger
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct LoggerSpec {
+    pub name: String,
+    #[serde(default)]
+    pub level: Option<String>,
+    #[serde(default)]
+    pub formatter: FormatterSpec,
+    /// Defaults to a single console output
+    #[serde(default)]
+    pub outputs: Vec<OutputSpec>,
+    /// Apply the default [`Redactor`]
+    #[serde(default)]
+    pub redact: bool,
+}
+
+/// Declarative logging configuration
+///
+/// # Examples
+///
+/// ```
+/// use mudssky_utils::logger::LoggingConfig;
+///
+/// let config = LoggingConfig::from_json_str(r#"{
+///     "level": "info",
+///     "filter": "config_doc::db=debug",
+///     "loggers": [
+///         {
+///             "name": "config_doc",
+///             "formatter": { "type": "json" },
+///             "outputs": [
+///                 { "type": "console" },
+///                 { "type": "console", "level": "error", "formatter": { "type": "logfmt" } }
+///             ]
+///         }
+///     ]
+/// }"#).unwrap();
+///
+/// let loggers = config.apply().unwrap();
+/// assert_eq!(loggers[0].name(), "config_doc");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct LoggingConfig {
+    /// Level for loggers that do not set their own
+    #[serde(default)]
+    pub level: Option<String>,
+    /// Filter directives applied after the loggers are registered
+    #[serde(default)]
+    pub filter: Option<String>,
+    #[serde(default)]
+    pub loggers: Vec<LoggerSpec>,
+}
+
+impl LoggingConfig {
+    /// Parse a configuration from JSON text
+    pub fn from_json_str(json: &str) -> Result<Self, ConfigError> {
+        serde_json::from_str(json).map_err(|e| ConfigError::new("<json>", e.to_string()))
+    }
+
+    /// Load a configuration from a JSON file
+    pub fn from_json_file(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| ConfigError::new(path.display().to_string(), e.to_string()))?;
+        Self::from_json_str(&content)
+    }
+
+    /// Build a configuration from `MUDSSKY_LOG_*` environment variables
+    ///
+    /// See [`LoggingConfig::from_vars`] for the recognised variables.
+    pub fn from_env() -> Result<Self, ConfigError> {
+        Self::from_vars(std::env::vars())
+    }
+
+    /// Build a configuration from `MUDSSKY_LOG_*` variables
+    ///
+    /// - `MUDSSKY_LOG_CONFIG`: path of a JSON file; when set the other
+    ///   variables are ignored
+    /// - `MUDSSKY_LOG_NAME`: logger name (default `app`)
+    /// - `MUDSSKY_LOG_LEVEL`: level
+    /// - `MUDSSKY_LOG_FILTER`: filter directives
+    /// - `MUDSSKY_LOG_FORMAT`: `simple`, `json`, `logfmt` or `colored`
+    /// - `MUDSSKY_LOG_PATTERN`: layout for a pattern formatter (overrides format)
+    /// - `MUDSSKY_LOG_FILE`: write to this file instead of the console
+    /// - `MUDSSKY_LOG_REDACT`: `true` to enable the default redactor
+    ///
+    /// Values are validated here, so errors name the variable that failed.
+    pub fn from_vars(
+        vars: impl IntoIterator<Item = (String, String)>,
+    ) -> Result<Self, ConfigError> {
+        let vars: HashMap<String, String> = vars
+            .into_iter()
+            .filter_map(|(k, v)| k.strip_prefix(LOG_ENV_PREFIX).map(|k| (k.to_string(), v)))
+            .collect();
+
+        if let Some(path) = vars.get("CONFIG") {
+            return Self::from_json_file(path);
+        }
+        if vars.is_empty() {
+            return Ok(Self::default());
+        }
+
+        let key = |name: &str| format!("{LOG_ENV_PREFIX}{name}");
+        let formatter = match (
+            vars.get("PATTERN"),
+            vars.get("FORMAT").map(|f| f.to_lowercase()),
+        ) {
+            (Some(pattern), _) => FormatterSpec::Pattern {
+                pattern: pattern.clone(),
+            },
+            (None, None) => FormatterSpec::Simple,
+            (None, Some(format)) => match format.as_str() {
+                "simple" => FormatterSpec::Simple,
+                "json" => FormatterSpec::Json,
+                "logfmt" => FormatterSpec::Logfmt,
+                "colored" => FormatterSpec::Colored { color: None },
+                other => {
+                    return Err(ConfigError::new(
+                        key("FORMAT"),
+                        format!(
+                            "unknown format '{other}', expected simple, json, logfmt or colored"
+                        ),
+                    ));
+                }
+            },
+        };
+        let outputs = match vars.get("FILE") {
+            Some(path) => vec![OutputSpec {
+                kind: OutputKind::File {
+                    path: path.clone(),
+                    max_size: None,
+                    rotate_daily: None,
+                    max_files: None,
+                    archive_pattern: None,
+                },
+                level: None,
+                formatter: None,
+            }],
+            None => Vec::new(),
+        };
+        let redact = match vars.get("REDACT").map(|v| v.to_lowercase()) {
+            None => false,
+            Some(v) if matches!(v.as_str(), "1" | "true" | "yes" | "on") => true,
+            Some(v) if matches!(v.as_str(), "0" | "false" | "no" | "off") => false,
+            Some(v) => {
+                return Err(ConfigError::new(
+                    key("REDACT"),
+                    format!("expected a boolean, got '{v}'"),
+                ));
+            }
+        };
+
+        let config = Self {
+            level: None,
+            filter: vars.get("FILTER").cloned(),
+            loggers: vec![LoggerSpec {
+                name: vars.get("NAME").cloned().unwrap_or_else(|| "app".to_string()),
+                level: vars.get("LEVEL").cloned(),
+                formatter,
+                outputs,
+                redact,
+            }],
+        };
+        config.validate().map_err(|e| match env_var_for(e.key()) {
+            Some(var) => ConfigError::new(key(var), e.message()),
+            None => e,
+        })?;
+        Ok(config)
+    }
+
+    /// Check the whole configuration without creating any output
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        self.plan().map(|_| ())
+    }
+
+    /// Build every logger without registering anything
+    ///
+    /// The whole configuration is validated before any output is created.
+    /// Creating a file output creates its directory and opens the file, so an
+    /// I/O error on a later file output can leave earlier files behind.
+    pub fn build(&self) -> Result<Vec<LoggerConfig>, ConfigError> {
+        self.plan()?.into_iter().map(LoggerPlan::build).collect()
+    }
+
+    /// Build, register and filter the configured loggers
+    ///
+    /// Nothing is registered when any part of the configuration is invalid.
+    pub fn apply(&self) -> Result<Vec<Logger>, ConfigError> {
+        let configs = self.build()?;
+        let loggers = configs.into_iter().map(create_logger).collect();
+        if let Some(filter) = &self.filter {
+            set_filter(
+                LogFilter::parse(filter).map_err(|e| ConfigError::new("filter", e.to_string()))?,
+            );
+        }
+        Ok(loggers)
+    }
+
+    fn plan(&self) -> Result<Vec<LoggerPlan>, ConfigError> {
+        let default_level = match &self.level {
+            Some(level) => Some(parse_level("level", level)?),
+            None => None,
+        };
+        if let Some(filter) = &self.filter {
+            LogFilter::parse(filter).map_err(|e| ConfigError::new("filter", e.to_string()))?;
+        }
+
+        self.loggers
+            .iter()
+            .enumerate()
+            .map(|(index, spec)| plan_logger(&format!("loggers[{index}]"), spec, default_level))
+            .collect()
+    }
+}
+
+/// Variable behind a key of a configuration built by [`LoggingConfig::from_vars`]
+fn env_var_for(key: &str) -> Option<&'static str> {
+    match key {
+        "filter" => Some("FILTER"),
+        "loggers[0].name" => Some("NAME"),
+        "loggers[0].level" => Some("LEVEL"),
+        "loggers[0].formatter.pattern" => Some("PATTERN"),
+        "loggers[0].outputs[0].path" => Some("FILE"),
+        _ => None,
+    }
+}
+
+fn parse_level(key: &str, level: &str) -> Result<LogLevel, ConfigError> {
+    level.parse().map_err(|e: String| ConfigError::new(key, e))
+}
+
+/// Deserialize a field where `null` differs from a missing field
+fn explicit_null<'de, D, T>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+/// Logger settings checked before any output is created
+struct LoggerPlan {
+    name: String,
+    level: LogLevel,
+    formatter: Arc<dyn LogFormatter>,
+    sinks: Vec<SinkPlan>,
+    redact: bool,
+}
+
+struct SinkPlan {
+    key: String,
+    output: OutputPlan,
+    level: Option<LogLevel>,
+    formatter: Option<Arc<dyn LogFormatter>>,
+}
+
+enum OutputPlan {
+    Console,
+    SplitConsole(LogLevel),
+    File(String, FileOutputOptions),
+}
+
+fn plan_logger(
+    key: &str,
+    spec: &LoggerSpec,
+    default_level: Option<LogLevel>,
+) -> Result<LoggerPlan, ConfigError> {
+    if spec.name.is_empty() {
+        return Err(ConfigError::new(
+            format!("{key}.name"),
+            "logger name must not be empty",
+        ));
+    }
+    let level = match &spec.level {
+        Some(level) => parse_level(&format!("{key}.level"), level)?,
+        None => default_level.unwrap_or(LogLevel::Info),
+    };
+    let formatter = build_formatter(&format!("{key}.formatter"), &spec.formatter)?;
+
+    let mut sinks = Vec::new();
+    for (index, output) in spec.outputs.iter().enumerate() {
+        let output_key = format!("{key}.outputs[{index}]");
+        let level = match &output.level {
+            Some(level) => Some(parse_level(&format!("{output_key}.level"), level)?),
+            None => None,
+        };
+        let formatter = match &output.formatter {
+            Some(formatter) => Some(build_formatter(
+                &format!("{output_key}.formatter"),
+                formatter,
+            )?),
+            None => None,
+        };
+        sinks.push(SinkPlan {
+            output: plan_output(&output_key, &output.kind)?,
+            key: output_key,
+            level,
+            formatter,
+        });
+    }
+
+    Ok(LoggerPlan {
+        name: spec.name.clone(),
+        level,
+        formatter,
+        sinks,
+        redact: spec.redact,
+    })
+}
+
+impl LoggerPlan {
+    fn build(self) -> Result<LoggerConfig, ConfigError> {
+        let mut outputs = Vec::new();
+        for sink in self.sinks {
+            outputs.push((sink.output.build(&sink.key)?, sink.level, sink.formatter));
+        }
+
+        let output: Arc<dyn LogOutput> = match outputs.len() {
+            0 => Arc::new(ConsoleOutput),
+            1 if outputs[0].1.is_none() && outputs[0].2.is_none() => outputs.remove(0).0,
+            _ => {
+                let mut multi = MultiOutput::new();
+                for (output, sink_level, sink_formatter) in outputs {
+                    let sink_level = sink_level.unwrap_or(LogLevel::Trace);
+                    multi = match sink_formatter {
+                        Some(formatter) => multi.with_formatted_sink(output, sink_level, formatter),
+                        None => multi.with_sink(output, sink_level),
+                    };
+                }
+                Arc::new(multi)
+            }
+        };
+
+        let mut config = LoggerConfig::new(self.name)
+            .with_level(self.level)
+            .with_formatter(self.formatter)
+            .with_output(output);
+        if self.redact {
+            config = config.with_redactor(Redactor::default());
+        }
+        Ok(config)
+    }
+}
+
+fn build_formatter(key: &str, spec: &FormatterSpec) -> Result<Arc<dyn LogFormatter>, ConfigError> {
+    Ok(match spec {
+        FormatterSpec::Simple => Arc::new(SimpleFormatter::default()),
+        FormatterSpec::Json => Arc::new(JsonFormatter),
+        FormatterSpec::Logfmt => Arc::new(LogfmtFormatter::default()),
+        FormatterSpec::Colored { color } => {
+            let mode = match color.as_deref().map(str::to_lowercase).as_deref() {
+                None | Some("auto") => ColorMode::Auto,
+                Some("always") => ColorMode::Always,
+                Some("never") => ColorMode::Never,
+                Some(other) => {
+                    return Err(ConfigError::new(
+                        format!("{key}.color"),
+                        format!("unknown color mode '{other}', expected auto, always or never"),
+                    ));
+                }
+            };
+            Arc::new(ColoredFormatter::new(mode))
+        }
+        FormatterSpec::Pattern { pattern } => Arc::new(
+            PatternFormatter::new(pattern)
+                .map_err(|e| ConfigError::new(format!("{key}.pattern"), e.to_string()))?,
+        ),
+        FormatterSpec::Syslog { app_name, hostname } => {
+            let mut formatter = SyslogFormatter::new(app_name.clone());
+            if let Some(hostname) = hostname {
+                formatter = formatter.with_hostname(hostname.clone());
+            }
+            Arc::new(formatter)
+        }
+    })
+}
+
+fn plan_output(key: &str, kind: &OutputKind) -> Result<OutputPlan, ConfigError> {
+    Ok(match kind {
+        OutputKind::Console => OutputPlan::Console,
+        OutputKind::SplitConsole { stderr_level } => OutputPlan::SplitConsole(match stderr_level {
+            Some(level) => parse_level(&format!("{key}.stderr_level"), level)?,
+            None => LogLevel::Warn,
+        }),
+        OutputKind::File {
+            path,
+            max_size,
+            rotate_daily,
+            max_files,
+            archive_pattern,
+        } => {
+            if path.is_empty() {
+                return Err(ConfigError::new(
+                    format!("{key}.path"),
+                    "file path must not be empty",
+                ));
+            }
+            let defaults = FileOutputOptions::default();
+            let options = FileOutputOptions {
+                max_size: max_size.unwrap_or(defaults.max_size),
+                rotate_daily: rotate_daily.unwrap_or(defaults.rotate_daily),
+                max_files: max_files.unwrap_or(defaults.max_files),
+                archive_pattern: archive_pattern.clone().unwrap_or(defaults.archive_pattern),
+            };
+            options
+                .check()
+                .map_err(|e| ConfigError::new(format!("{key}.archive_pattern"), e))?;
+            OutputPlan::File(path.clone(), options)
+        }
+    })
+}
+
+impl OutputPlan {
+    fn build(self, key: &str) -> Result<Arc<dyn LogOutput>, ConfigError> {
+        Ok(match self {
+            OutputPlan::Console => Arc::new(ConsoleOutput),
+            OutputPlan::SplitConsole(level) => Arc::new(SplitConsoleOutput::new(level)),
+            OutputPlan::File(path, options) => Arc::new(
+                FileOutput::with_options(&path, options)
+                    .map_err(|e| ConfigError::new(format!("{key}.path"), e.to_string()))?,
+            ),
+        })
+    }
+}
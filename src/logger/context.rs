@@ -0,0 +1,135 @@
+//! Scoped log context (MDC)
+//!
+//! Fields pushed into the context are merged into the metadata of every entry
+//! logged within the scope. Thread-local scopes cover synchronous code; task
+//! scopes follow a tokio task across `.await` points and threads. Explicit
+//! entry metadata wins over context fields, inner scopes win over outer ones,
+//! and task fields win over thread fields.
+
+use serde_json::Value;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::future::Future;
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+thread_local! {
+    static THREAD_CONTEXT: RefCell<Vec<HashMap<String, Value>>> = const { RefCell::new(Vec::new()) };
+}
+
+tokio::task_local! {
+    static TASK_CONTEXT: Arc<HashMap<String, Value>>;
+}
+
+fn collect_fields<K: Into<String>>(
+    fields: impl IntoIterator<Item = (K, Value)>,
+) -> HashMap<String, Value> {
+    fields.into_iter().map(|(k, v)| (k.into(), v)).collect()
+}
+
+/// Removes a thread-local context scope when dropped
+///
+/// The guard is tied to the thread that created it and cannot be sent.
+#[derive(Debug)]
+pub struct ContextGuard {
+    depth: usize,
+    _not_send: PhantomData<*const ()>,
+}
+
+impl Drop for ContextGuard {
+    fn drop(&mut self) {
+        THREAD_CONTEXT.with(|stack| stack.borrow_mut().truncate(self.depth));
+    }
+}
+
+/// Push fields onto the current thread's context until the guard is dropped
+///
+/// # Examples
+///
+/// ```
+/// use mudssky_utils::logger::{current_context, push_context};
+/// use serde_json::json;
+///
+/// {
+///     let _guard = push_context([("request_id", json!("abc"))]);
+///     assert_eq!(current_context()["request_id"], json!("abc"));
+/// }
+/// assert!(current_context().is_empty());
+/// ```
+pub fn push_context<K: Into<String>>(fields: impl IntoIterator<Item = (K, Value)>) -> ContextGuard {
+    let fields = collect_fields(fields);
+    let depth = THREAD_CONTEXT.with(|stack| {
+        let mut stack = stack.borrow_mut();
+        stack.push(fields);
+        stack.len() - 1
+    });
+    ContextGuard {
+        depth,
+        _not_send: PhantomData,
+    }
+}
+
+/// Run a closure with fields pushed onto the current thread's context
+pub fn with_thread_context<K, F, R>(fields: impl IntoIterator<Item = (K, Value)>, f: F) -> R
+where
+    K: Into<String>,
+    F: FnOnce() -> R,
+{
+    let _guard = push_context(fields);
+    f()
+}
+
+/// Run a future with fields attached to its task context
+///
+/// Nested calls inherit the fields of the enclosing task scope.
+///
+/// # Examples
+///
+/// ```
+/// use mudssky_utils::logger::{current_context, with_task_context};
+/// use serde_json::json;
+///
+/// # #[tokio::main]
+/// # async fn main() {
+/// with_task_context([("tenant", json!("acme"))], async {
+///     tokio::task::yield_now().await;
+///     assert_eq!(current_context()["tenant"], json!("acme"));
+/// })
+/// .await;
+/// # }
+/// ```
+pub async fn with_task_context<K, F>(
+    fields: impl IntoIterator<Item = (K, Value)>,
+    future: F,
+) -> F::Output
+where
+    K: Into<String>,
+    F: Future,
+{
+    let mut merged = TASK_CONTEXT.try_with(|ctx| ctx.as_ref().clone()).unwrap_or_default();
+    merged.extend(collect_fields(fields));
+    TASK_CONTEXT.scope(Arc::new(merged), future).await
+}
+
+/// Get the merged context fields visible at this point
+pub fn current_context() -> HashMap<String, Value> {
+    let mut fields = HashMap::new();
+    merge_current_context(&mut fields);
+    fields
+}
+
+/// Add context fields that are not already present in `metadata`
+pub(super) fn merge_current_context(metadata: &mut HashMap<String, Value>) {
+    let _ = TASK_CONTEXT.try_with(|ctx| {
+        for (key, value) in ctx.iter() {
+            metadata.entry(key.clone()).or_insert_with(|| value.clone());
+        }
+    });
+    THREAD_CONTEXT.with(|stack| {
+        for frame in stack.borrow().iter().rev() {
+            for (key, value) in frame {
+                metadata.entry(key.clone()).or_insert_with(|| value.clone());
+            }
+        }
+    });
+}
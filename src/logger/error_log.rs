@@ -0,0 +1,126 @@
+//! Error-chain aware logging
+
+use super::{LogEntry, LogLevel, Logger};
+use crate::error::{
+    ArgumentError, ConfigError, NetworkError, ParseError, UtilsError, ValidationError,
+};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::error::Error;
+
+/// Describe an error as log metadata
+///
+/// Produces `error` (top-level message), `error_chain` (messages of the error
+/// and every `source()`), and, for the first error in the chain that is a
+/// [`UtilsError`] or one of the error types from [`crate::error`],
+/// `error_kind` plus structured fields such as `error_field` or
+/// `error_status_code`.
+///
+/// # Examples
+///
+/// ```
+/// use mudssky_utils::error::{NetworkError, UtilsError};
+/// use mudssky_utils::logger::error_metadata;
+/// use serde_json::json;
+///
+/// let err = UtilsError::from(NetworkError::with_status("fetch", "bad gateway", 502));
+/// let metadata = error_metadata(&err);
+/// assert_eq!(metadata["error_kind"], json!("Network"));
+/// assert_eq!(metadata["error_status_code"], json!(502));
+/// ```
+pub fn error_metadata(error: &(dyn Error + 'static)) -> HashMap<String, Value> {
+    let mut metadata = HashMap::new();
+    metadata.insert("error".to_string(), json!(error.to_string()));
+
+    let mut chain = Vec::new();
+    let mut described = false;
+    let mut current = Some(error);
+    while let Some(err) = current {
+        chain.push(json!(err.to_string()));
+        if !described {
+            described = describe(err, &mut metadata);
+        }
+        current = err.source();
+    }
+    metadata.insert("error_chain".to_string(), Value::Array(chain));
+    metadata
+}
+
+/// Add kind and fields for known error types; returns whether `error` was known
+fn describe(error: &(dyn Error + 'static), metadata: &mut HashMap<String, Value>) -> bool {
+    let mut insert = |key: &str, value: Value| {
+        metadata.insert(format!("error_{key}"), value);
+    };
+
+    if let Some(err) = error.downcast_ref::<UtilsError>() {
+        match err {
+            UtilsError::Argument(e) => return describe(e, metadata),
+            UtilsError::Validation(e) => return describe(e, metadata),
+            UtilsError::Config(e) => return describe(e, metadata),
+            UtilsError::Network(e) => return describe(e, metadata),
+            UtilsError::Parse(e) => return describe(e, metadata),
+            UtilsError::Io(e) => {
+                insert("kind", json!(err.kind()));
+                insert("io_kind", json!(format!("{:?}", e.kind())));
+            }
+            UtilsError::Other(_) => insert("kind", json!(err.kind())),
+        }
+    } else if let Some(err) = error.downcast_ref::<ArgumentError>() {
+        insert("kind", json!("Argument"));
+        insert("message", json!(err.message));
+    } else if let Some(err) = error.downcast_ref::<ValidationError>() {
+        insert("kind", json!("Validation"));
+        insert("field", json!(err.field()));
+        insert("message", json!(err.message()));
+        if let Some(value) = err.value() {
+            insert("value", json!(value));
+        }
+    } else if let Some(err) = error.downcast_ref::<ConfigError>() {
+        insert("kind", json!("Config"));
+        insert("key", json!(err.key()));
+        insert("message", json!(err.message()));
+    } else if let Some(err) = error.downcast_ref::<NetworkError>() {
+        insert("kind", json!("Network"));
+        insert("operation", json!(err.operation()));
+        insert("message", json!(err.message()));
+        if let Some(status_code) = err.status_code() {
+            insert("status_code", json!(status_code));
+        }
+    } else if let Some(err) = error.downcast_ref::<ParseError>() {
+        insert("kind", json!("Parse"));
+        insert("input", json!(err.input()));
+        insert("expected", json!(err.expected()));
+        if let Some(position) = err.position() {
+            insert("position", json!(position));
+        }
+    } else if let Some(err) = error.downcast_ref::<std::io::Error>() {
+        insert("kind", json!("Io"));
+        insert("io_kind", json!(format!("{:?}", err.kind())));
+    } else {
+        return false;
+    }
+    true
+}
+
+impl Logger {
+    /// Log a message together with an error and its `source()` chain
+    ///
+    /// See [`error_metadata`] for the recorded fields.
+    pub fn log_error(&self, level: LogLevel, message: &str, error: &(dyn Error + 'static)) {
+        if self.is_enabled(level) {
+            let entry = LogEntry::new(level, self.name().to_string(), message.to_string())
+                .with_metadata_map(error_metadata(error));
+            self.log_entry(entry);
+        }
+    }
+
+    /// Log an error at `ERROR` level
+    pub fn error_with(&self, message: &str, error: &(dyn Error + 'static)) {
+        self.log_error(LogLevel::Error, message, error);
+    }
+
+    /// Log an error at `WARN` level
+    pub fn warn_with(&self, message: &str, error: &(dyn Error + 'static)) {
+        self.log_error(LogLevel::Warn, message, error);
+    }
+}
@@ -0,0 +1,291 @@
+//! File output with size and daily rotation
+
+use super::LogOutput;
+use chrono::{DateTime, NaiveDate, Utc};
+use regex::Regex;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// File output options
+#[derive(Debug, Clone)]
+pub struct FileOutputOptions {
+    /// Rotate when the active file would grow beyond this many bytes
+    pub max_size: Option<u64>,
+    /// Rotate when the (UTC) day changes
+    pub rotate_daily: bool,
+    /// Number of archived files to keep
+    pub max_files: usize,
+    /// Archive file name pattern
+    ///
+    /// Supported placeholders: `{filename}` (full active file name), `{stem}`,
+    /// `{ext}` (extension including the leading dot, or empty), `{date}`
+    /// (`YYYY-MM-DD` of the rotated period) and `{index}` (first free number from 1).
+    /// The pattern must contain `{index}` so that archives never overwrite each other.
+    pub archive_pattern: String,
+}
+
+impl FileOutputOptions {
+    /// Check the options for mistakes that would lose archived logs
+    pub(crate) fn check(&self) -> Result<(), &'static str> {
+        if self.archive_pattern.contains("{index}") {
+            Ok(())
+        } else {
+            Err("archive pattern must contain {index}")
+        }
+    }
+}
+
+impl Default for FileOutputOptions {
+    fn default() -> Self {
+        Self {
+            max_size: Some(10 * 1024 * 1024),
+            rotate_daily: false,
+            max_files: 7,
+            archive_pattern: "{stem}.{date}.{index}{ext}".to_string(),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct FileState {
+    file: Option<File>,
+    size: u64,
+    period: NaiveDate,
+}
+
+/// Appending file output with optional rotation
+///
+/// All writes go through an internal mutex, so a single instance can be shared
+/// between loggers via `Arc<dyn LogOutput>`.
+#[derive(Debug)]
+pub struct FileOutput {
+    path: PathBuf,
+    options: FileOutputOptions,
+    archive_regex: Regex,
+    state: Mutex<FileState>,
+}
+
+impl FileOutput {
+    /// Create a file output with default options
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use mudssky_utils::logger::{FileOutput, LoggerConfig};
+    /// use std::sync::Arc;
+    ///
+    /// let output = FileOutput::new("logs/app.log").unwrap();
+    /// let config = LoggerConfig::new("app".to_string()).with_output(Arc::new(output));
+    /// ```
+    pub fn new(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::with_options(path, FileOutputOptions::default())
+    }
+
+    /// Create a file output with custom options
+    ///
+    /// Fails with [`io::ErrorKind::InvalidInput`] if the archive pattern has no
+    /// `{index}` placeholder. When the file already has content, its modification
+    /// time decides the day it belongs to for daily rotation.
+    pub fn with_options(path: impl AsRef<Path>, options: FileOutputOptions) -> io::Result<Self> {
+        options.check().map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+        let path = path.as_ref().to_path_buf();
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+
+        let (stem, ext) = split_file_name(&path);
+        let archive_regex = Regex::new(&archive_name_regex(
+            &options.archive_pattern,
+            &file_name(&path),
+            &stem,
+            &ext,
+        ))
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+
+        let file = open_append(&path)?;
+        let metadata = file.metadata()?;
+        let size = metadata.len();
+        let period = match metadata.modified() {
+            Ok(modified) if size > 0 => DateTime::<Utc>::from(modified).date_naive(),
+            _ => Utc::now().date_naive(),
+        };
+
+        Ok(Self {
+            path,
+            options,
+            archive_regex,
+            state: Mutex::new(FileState {
+                file: Some(file),
+                size,
+                period,
+            }),
+        })
+    }
+
+    /// Get the path of the active log file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Rotate the active file immediately
+    pub fn rotate(&self) -> io::Result<()> {
+        let mut state = self.state.lock().unwrap();
+        let period = state.period;
+        self.rotate_locked(&mut state, period)
+    }
+
+    /// List archived files, newest first
+    pub fn archived_files(&self) -> io::Result<Vec<PathBuf>> {
+        let dir = self.directory();
+        let mut archives = Vec::new();
+
+        for entry in fs::read_dir(&dir)? {
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().to_string();
+            if name != file_name(&self.path) && self.archive_regex.is_match(&name) {
+                let modified = entry.metadata()?.modified()?;
+                archives.push((modified, entry.path()));
+            }
+        }
+
+        archives.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| b.1.cmp(&a.1)));
+        Ok(archives.into_iter().map(|(_, path)| path).collect())
+    }
+
+    fn directory(&self) -> PathBuf {
+        match self.path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+            _ => PathBuf::from("."),
+        }
+    }
+
+    fn write_line(&self, line: &str) -> io::Result<()> {
+        let mut state = self.state.lock().unwrap();
+        let today = Utc::now().date_naive();
+        let len = line.len() as u64 + 1;
+
+        if self.options.rotate_daily && today != state.period {
+            let period = state.period;
+            self.rotate_locked(&mut state, period)?;
+        } else if let Some(max_size) = self.options.max_size {
+            if state.size > 0 && state.size + len > max_size {
+                let period = state.period;
+                self.rotate_locked(&mut state, period)?;
+            }
+        }
+        state.period = today;
+
+        if state.file.is_none() {
+            state.file = Some(open_append(&self.path)?);
+        }
+        let file = state.file.as_mut().unwrap();
+        file.write_all(line.as_bytes())?;
+        file.write_all(b"\n")?;
+        state.size += len;
+        Ok(())
+    }
+
+    fn rotate_locked(&self, state: &mut FileState, period: NaiveDate) -> io::Result<()> {
+        // Close the handle before renaming so rotation also works on Windows
+        state.file = None;
+
+        if self.path.exists() {
+            let archive = self.next_archive_path(period);
+            fs::rename(&self.path, archive)?;
+        }
+
+        state.file = Some(open_append(&self.path)?);
+        state.size = 0;
+        self.prune()
+    }
+
+    fn next_archive_path(&self, period: NaiveDate) -> PathBuf {
+        let (stem, ext) = split_file_name(&self.path);
+        let date = period.format("%Y-%m-%d").to_string();
+        let dir = self.directory();
+
+        let mut index = 1;
+        loop {
+            let name = self
+                .options
+                .archive_pattern
+                .replace("{filename}", &file_name(&self.path))
+                .replace("{stem}", &stem)
+                .replace("{ext}", &ext)
+                .replace("{date}", &date)
+                .replace("{index}", &index.to_string());
+            let candidate = dir.join(name);
+            if !candidate.exists() {
+                return candidate;
+            }
+            index += 1;
+        }
+    }
+
+    fn prune(&self) -> io::Result<()> {
+        for path in self.archived_files()?.into_iter().skip(self.options.max_files) {
+            fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+}
+
+impl LogOutput for FileOutput {
+    fn write(&self, formatted_message: &str) {
+        if let Err(e) = self.write_line(formatted_message) {
+            eprintln!(
+                "FileOutput: failed to write to {}: {e}",
+                self.path.display()
+            );
+        }
+    }
+
+    fn flush(&self) {
+        if let Some(file) = self.state.lock().unwrap().file.as_mut() {
+            let _ = file.flush();
+        }
+    }
+}
+
+fn open_append(path: &Path) -> io::Result<File> {
+    OpenOptions::new().create(true).append(true).open(path)
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default()
+}
+
+fn split_file_name(path: &Path) -> (String, String) {
+    let stem = path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+    let ext = path
+        .extension()
+        .map(|e| format!(".{}", e.to_string_lossy()))
+        .unwrap_or_default();
+    (stem, ext)
+}
+
+/// Build a regex that matches archive names produced by `pattern`
+fn archive_name_regex(pattern: &str, filename: &str, stem: &str, ext: &str) -> String {
+    let placeholder = Regex::new(r"\{(filename|stem|ext|date|index)\}").unwrap();
+    let mut result = String::from("^");
+    let mut last = 0;
+
+    for caps in placeholder.captures_iter(pattern) {
+        let whole = caps.get(0).unwrap();
+        result.push_str(&regex::escape(&pattern[last..whole.start()]));
+        match &caps[1] {
+            "filename" => result.push_str(&regex::escape(filename)),
+            "stem" => result.push_str(&regex::escape(stem)),
+            "ext" => result.push_str(&regex::escape(ext)),
+            "date" => result.push_str(r"\d{4}-\d{2}-\d{2}"),
+            _ => result.push_str(r"\d+"),
+        }
+        last = whole.end();
+    }
+
+    result.push_str(&regex::escape(&pattern[last..]));
+    result.push('$');
+    result
+}
@@ -0,0 +1,189 @@
+//! `RUST_LOG`-style per-target level filtering
+
+use super::{LOGGER_REGISTRY, LogLevel};
+use crate::error::ParseError;
+use once_cell::sync::Lazy;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::RwLock;
+
+/// Environment variable read by [`apply_filter_from_env`] when no name is given
+pub const DEFAULT_FILTER_ENV: &str = "RUST_LOG";
+
+/// A single `target=level` directive
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilterDirective {
+    pub target: String,
+    pub level: LogLevel,
+}
+
+/// Parsed filter specification such as `info,db=debug,http::client=warn`
+///
+/// A bare level sets the default, a bare target enables it at `TRACE`. Logger
+/// names are hierarchical (`app::db::pool`) and take the level of the most
+/// specific matching directive.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LogFilter {
+    default_level: Option<LogLevel>,
+    directives: Vec<FilterDirective>,
+}
+
+impl LogFilter {
+    /// Parse a filter specification
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use mudssky_utils::logger::{LogFilter, LogLevel};
+    ///
+    /// let filter = LogFilter::parse("info,db=debug,http::client=warn").unwrap();
+    /// assert_eq!(filter.level_for("db::pool"), Some(LogLevel::Debug));
+    /// assert_eq!(filter.level_for("http::client"), Some(LogLevel::Warn));
+    /// assert_eq!(filter.level_for("http"), Some(LogLevel::Info));
+    /// ```
+    pub fn parse(spec: &str) -> Result<Self, ParseError> {
+        let mut filter = LogFilter::default();
+        let mut offset = 0;
+
+        for part in spec.split(',') {
+            let position = offset + (part.len() - part.trim_start().len());
+            offset += part.len() + 1;
+
+            let part = part.trim();
+            if part.is_empty() {
+                continue;
+            }
+
+            match part.split_once('=') {
+                Some((target, level)) => {
+                    let target = target.trim();
+                    if target.is_empty() {
+                        return Err(ParseError::with_position(part, "target name", position));
+                    }
+                    let level = level
+                        .trim()
+                        .parse::<LogLevel>()
+                        .map_err(|_| ParseError::with_position(part, "target=level", position))?;
+                    filter.directives.push(FilterDirective {
+                        target: target.to_string(),
+                        level,
+                    });
+                }
+                None => match part.parse::<LogLevel>() {
+                    Ok(level) => filter.default_level = Some(level),
+                    Err(_) => filter.directives.push(FilterDirective {
+                        target: part.to_string(),
+                        level: LogLevel::Trace,
+                    }),
+                },
+            }
+        }
+
+        Ok(filter)
+    }
+
+    /// Get the level set by a bare level directive
+    pub fn default_level(&self) -> Option<LogLevel> {
+        self.default_level
+    }
+
+    /// Get the target directives in the order they were given
+    pub fn directives(&self) -> &[FilterDirective] {
+        &self.directives
+    }
+
+    /// Resolve the level for a logger name
+    ///
+    /// Returns `None` when neither a directive nor a default level applies.
+    pub fn level_for(&self, name: &str) -> Option<LogLevel> {
+        self.directives
+            .iter()
+            .filter(|d| target_matches(&d.target, name))
+            // Later directives win over earlier ones of equal specificity
+            .max_by_key(|d| d.target.len())
+            .map(|d| d.level)
+            .or(self.default_level)
+    }
+}
+
+impl FromStr for LogFilter {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        LogFilter::parse(s)
+    }
+}
+
+impl fmt::Display for LogFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = Vec::new();
+        if let Some(level) = self.default_level {
+            parts.push(level.to_string().to_lowercase());
+        }
+        for directive in &self.directives {
+            parts.push(format!(
+                "{}={}",
+                directive.target,
+                directive.level.to_string().to_lowercase()
+            ));
+        }
+        write!(f, "{}", parts.join(","))
+    }
+}
+
+fn target_matches(target: &str, name: &str) -> bool {
+    name == target || (name.starts_with(target) && name[target.len()..].starts_with("::"))
+}
+
+/// Filter applied to loggers created after [`set_filter`]
+static ACTIVE_FILTER: Lazy<RwLock<Option<LogFilter>>> = Lazy::new(|| RwLock::new(None));
+
+/// Level the active filter assigns to `name`, if any
+pub(super) fn active_level_for(name: &str) -> Option<LogLevel> {
+    ACTIVE_FILTER.read().unwrap().as_ref().and_then(|f| f.level_for(name))
+}
+
+/// Apply a filter to every registered logger
+///
+/// The filter is remembered and also applied to loggers registered later, so it
+/// can be re-applied at runtime to change verbosity without restarting. Loggers
+/// that no directive matches keep their current level.
+pub fn set_filter(filter: LogFilter) {
+    let registry = LOGGER_REGISTRY.lock().unwrap();
+    for (name, logger) in registry.iter() {
+        if let Some(level) = filter.level_for(name) {
+            logger.set_level(level);
+        }
+    }
+    *ACTIVE_FILTER.write().unwrap() = Some(filter);
+}
+
+/// Parse a specification and apply it with [`set_filter`]
+pub fn set_filter_spec(spec: &str) -> Result<(), ParseError> {
+    set_filter(LogFilter::parse(spec)?);
+    Ok(())
+}
+
+/// Get the currently active filter
+pub fn active_filter() -> Option<LogFilter> {
+    ACTIVE_FILTER.read().unwrap().clone()
+}
+
+/// Forget the active filter; levels already applied are left unchanged
+pub fn clear_filter() {
+    *ACTIVE_FILTER.write().unwrap() = None;
+}
+
+/// Read a filter specification from an environment variable and apply it
+///
+/// Uses [`DEFAULT_FILTER_ENV`] when `var` is `None`. Returns `Ok(false)` when the
+/// variable is not set.
+pub fn apply_filter_from_env(var: Option<&str>) -> Result<bool, ParseError> {
+    match std::env::var(var.unwrap_or(DEFAULT_FILTER_ENV)) {
+        Ok(spec) => {
+            set_filter_spec(&spec)?;
+            Ok(true)
+        }
+        Err(_) => Ok(false),
+    }
+}
@@ -0,0 +1,125 @@
+//! Bridge from the `log` crate facade into the logger registry
+
+use super::{LOGGER_REGISTRY, LogEntry, LogLevel, Logger, filter, resolve_logger};
+use once_cell::sync::Lazy;
+use serde_json::json;
+use std::sync::RwLock;
+
+impl From<log::Level> for LogLevel {
+    fn from(level: log::Level) -> Self {
+        match level {
+            log::Level::Trace => LogLevel::Trace,
+            log::Level::Debug => LogLevel::Debug,
+            log::Level::Info => LogLevel::Info,
+            log::Level::Warn => LogLevel::Warn,
+            log::Level::Error => LogLevel::Error,
+        }
+    }
+}
+
+impl From<LogLevel> for log::Level {
+    fn from(level: LogLevel) -> Self {
+        match level {
+            LogLevel::Trace => log::Level::Trace,
+            LogLevel::Debug => log::Level::Debug,
+            LogLevel::Info => log::Level::Info,
+            LogLevel::Warn => log::Level::Warn,
+            LogLevel::Error => log::Level::Error,
+        }
+    }
+}
+
+/// `log::Log` implementation that routes records into registered loggers
+///
+/// The record target is used as the logger name; targets without their own
+/// logger use the nearest registered ancestor (`app::db` for `app::db::pool`).
+/// Records whose target matches no registered logger go to the fallback set
+/// with [`set_log_bridge_fallback`], or are dropped. The bridge never registers
+/// loggers itself.
+///
+/// A level from the active filter for the exact target takes precedence over
+/// the level of the logger it resolves to. Module path, file and line are
+/// stored in `LogEntry::metadata`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LogBridge;
+
+static LOG_BRIDGE: LogBridge = LogBridge;
+
+/// Logger for records whose target matches no registered logger
+static FALLBACK_LOGGER: Lazy<RwLock<Option<Logger>>> = Lazy::new(|| RwLock::new(None));
+
+impl LogBridge {
+    /// Logger that should write a record for `target` at `level`, if any
+    fn route(target: &str, level: LogLevel) -> Option<Logger> {
+        let logger = resolve_logger(target).or_else(|| FALLBACK_LOGGER.read().unwrap().clone())?;
+        let threshold = filter::active_level_for(target).unwrap_or_else(|| logger.level());
+        (level >= threshold).then_some(logger)
+    }
+}
+
+impl log::Log for LogBridge {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        Self::route(metadata.target(), metadata.level().into()).is_some()
+    }
+
+    fn log(&self, record: &log::Record) {
+        let level = LogLevel::from(record.level());
+        let Some(logger) = Self::route(record.target(), level) else {
+            return;
+        };
+
+        let mut entry = LogEntry::new(
+            level,
+            record.target().to_string(),
+            record.args().to_string(),
+        );
+        if let Some(module_path) = record.module_path() {
+            entry = entry.with_metadata("module_path".to_string(), json!(module_path));
+        }
+        if let Some(file) = record.file() {
+            entry = entry.with_metadata("file".to_string(), json!(file));
+        }
+        if let Some(line) = record.line() {
+            entry = entry.with_metadata("line".to_string(), json!(line));
+        }
+        logger.dispatch(entry);
+    }
+
+    fn flush(&self) {
+        let registry = LOGGER_REGISTRY.lock().unwrap();
+        for logger in registry.values() {
+            logger.flush();
+        }
+        drop(registry);
+        if let Some(fallback) = FALLBACK_LOGGER.read().unwrap().as_ref() {
+            fallback.flush();
+        }
+    }
+}
+
+/// Route `log` records whose target matches no registered logger to `logger`
+///
+/// Pass `None` to drop such records again, which is the default. The fallback
+/// does not need to be registered.
+pub fn set_log_bridge_fallback(logger: Option<Logger>) {
+    *FALLBACK_LOGGER.write().unwrap() = logger;
+}
+
+/// Install [`LogBridge`] as the global `log` logger
+///
+/// The `log` max level is set to `TRACE`; filtering is left to the levels of the
+/// registered loggers. Fails if another `log` logger is already installed.
+///
+/// # Examples
+///
+/// ```
+/// use mudssky_utils::logger::init_log_bridge;
+///
+/// init_log_bridge().unwrap();
+/// log::info!(target: "app::db", "connected");
+/// ```
+pub fn init_log_bridge() -> Result<(), log::SetLoggerError> {
+    log::set_logger(&LOG_BRIDGE)?;
+    log::set_max_level(log::LevelFilter::Trace);
+    Ok(())
+}
@@ -0,0 +1,91 @@
+//! logfmt formatter
+
+use super::{LogEntry, LogFormatter};
+use serde_json::Value;
+
+/// Formatter producing logfmt lines (`key=value` pairs)
+///
+/// The fixed keys `ts`, `level`, `logger` and `msg` come first, followed by the
+/// metadata sorted by key. Values containing spaces, `=`, quotes or control
+/// characters are quoted and escaped.
+#[derive(Debug, Clone)]
+pub struct LogfmtFormatter {
+    pub include_timestamp: bool,
+    pub include_logger_name: bool,
+}
+
+impl Default for LogfmtFormatter {
+    fn default() -> Self {
+        Self {
+            include_timestamp: true,
+            include_logger_name: true,
+        }
+    }
+}
+
+impl LogFormatter for LogfmtFormatter {
+    fn format(&self, entry: &LogEntry) -> String {
+        let mut pairs = Vec::new();
+
+        if self.include_timestamp {
+            pairs.push(format!("ts={}", entry.timestamp.to_rfc3339()));
+        }
+        pairs.push(format!("level={}", entry.level.to_string().to_lowercase()));
+        if self.include_logger_name {
+            pairs.push(format!("logger={}", quote_value(&entry.logger_name)));
+        }
+        pairs.push(format!("msg={}", quote_value(&entry.message)));
+
+        let mut keys: Vec<&String> = entry.metadata.keys().collect();
+        keys.sort();
+        for key in keys {
+            let value = match &entry.metadata[key] {
+                Value::String(s) => s.clone(),
+                other => other.to_string(),
+            };
+            pairs.push(format!("{}={}", sanitize_key(key), quote_value(&value)));
+        }
+
+        pairs.join(" ")
+    }
+}
+
+fn sanitize_key(key: &str) -> String {
+    let key: String = key
+        .chars()
+        .map(|c| {
+            if c == '=' || c == '"' || c.is_whitespace() || c.is_control() {
+                '_'
+            } else {
+                c
+            }
+        })
+        .collect();
+    if key.is_empty() { "_".to_string() } else { key }
+}
+
+fn quote_value(value: &str) -> String {
+    let needs_quotes = value.is_empty()
+        || value
+            .chars()
+            .any(|c| c == ' ' || c == '=' || c == '"' || c == '\\' || c.is_control());
+    if !needs_quotes {
+        return value.to_string();
+    }
+
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if c.is_control() => quoted.push_str(&format!("\\u{{{:04x}}}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
@@ -0,0 +1,126 @@
+//! Structured logging macros
+//!
+//! The macros check [`Logger::is_enabled`](super::Logger::is_enabled) before the
+//! message is formatted, collect `key = value` fields into `LogEntry::metadata`
+//! and record the call site as `file`, `line` and `module_path`.
+
+use serde::Serialize;
+use serde_json::Value;
+
+/// Convert a field value for the logging macros
+#[doc(hidden)]
+pub fn __field_value<T: Serialize + ?Sized>(value: &T) -> Value {
+    serde_json::to_value(value).unwrap_or(Value::Null)
+}
+
+/// Log a message at a given level with optional structured fields
+///
+/// Fields come before the format string and accept any `Serialize` value. A
+/// field named `file`, `line` or `module_path` replaces the call-site value.
+///
+/// # Examples
+///
+/// ```
+/// use mudssky_utils::logger::{LogLevel, Logger};
+///
+/// let logger = Logger::with_name("app");
+/// let rows = 3;
+/// mudssky_utils::log!(logger, LogLevel::Info, user_id = 42, "saved {} rows", rows);
+/// mudssky_utils::log!(logger, LogLevel::Debug, "skipped: {rows}");
+/// ```
+#[macro_export]
+macro_rules! log {
+    ($logger:expr, $level:expr, $($rest:tt)+) => {{
+        let logger = &$logger;
+        let level: $crate::logger::LogLevel = $level;
+        if logger.is_enabled(level) {
+            let mut metadata = ::std::collections::HashMap::new();
+            // Call-site fields go in first so user fields with the same name win
+            metadata.insert(
+                ::std::string::String::from("file"),
+                $crate::logger::__field_value(file!()),
+            );
+            metadata.insert(
+                ::std::string::String::from("line"),
+                $crate::logger::__field_value(&line!()),
+            );
+            metadata.insert(
+                ::std::string::String::from("module_path"),
+                $crate::logger::__field_value(module_path!()),
+            );
+            $crate::__log_event!(@fields logger, level, metadata; $($rest)+);
+        }
+    }};
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __log_event {
+    (@fields $logger:ident, $level:ident, $metadata:ident; $key:ident = $value:expr, $($rest:tt)+) => {
+        $metadata.insert(
+            ::std::string::String::from(stringify!($key)),
+            $crate::logger::__field_value(&$value),
+        );
+        $crate::__log_event!(@fields $logger, $level, $metadata; $($rest)+);
+    };
+    (@fields $logger:ident, $level:ident, $metadata:ident; $($arg:tt)+) => {
+        let entry = $crate::logger::LogEntry::new(
+            $level,
+            $logger.name().to_string(),
+            format!($($arg)+),
+        )
+        .with_metadata_map($metadata);
+        $logger.log_entry(entry);
+    };
+}
+
+/// Log at `TRACE` level, see [`log!`](crate::log!)
+#[macro_export]
+macro_rules! trace {
+    ($logger:expr, $($rest:tt)+) => {
+        $crate::log!($logger, $crate::logger::LogLevel::Trace, $($rest)+)
+    };
+}
+
+/// Log at `DEBUG` level, see [`log!`](crate::log!)
+#[macro_export]
+macro_rules! debug {
+    ($logger:expr, $($rest:tt)+) => {
+        $crate::log!($logger, $crate::logger::LogLevel::Debug, $($rest)+)
+    };
+}
+
+/// Log at `INFO` level, see [`log!`](crate::log!)
+///
+/// # Examples
+///
+/// ```
+/// use mudssky_utils::info;
+/// use mudssky_utils::logger::Logger;
+///
+/// let logger = Logger::with_name("app");
+/// let n = 10;
+/// info!(logger, user_id = 42, "saved {} rows", n);
+/// ```
+#[macro_export]
+macro_rules! info {
+    ($logger:expr, $($rest:tt)+) => {
+        $crate::log!($logger, $crate::logger::LogLevel::Info, $($rest)+)
+    };
+}
+
+/// Log at `WARN` level, see [`log!`](crate::log!)
+#[macro_export]
+macro_rules! warn {
+    ($logger:expr, $($rest:tt)+) => {
+        $crate::log!($logger, $crate::logger::LogLevel::Warn, $($rest)+)
+    };
+}
+
+/// Log at `ERROR` level, see [`log!`](crate::log!)
+#[macro_export]
+macro_rules! error {
+    ($logger:expr, $($rest:tt)+) => {
+        $crate::log!($logger, $crate::logger::LogLevel::Error, $($rest)+)
+    };
+}
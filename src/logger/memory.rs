@@ -0,0 +1,176 @@
+//! In-memory capture of log entries, mainly for tests
+
+use super::{LogEntry, LogLevel, LogOutput, LogQuery};
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+#[derive(Debug, Clone)]
+struct CapturedLog {
+    entry: Option<LogEntry>,
+    formatted: String,
+}
+
+/// Output that keeps the most recent entries in a bounded ring buffer
+///
+/// Entries arrive through [`LogOutput::write_entry`], so queries see the raw
+/// [`LogEntry`] rather than the formatted text. Lines written with plain
+/// [`LogOutput::write`] are only visible through [`MemoryOutput::formatted`].
+///
+/// # Examples
+///
+/// ```
+/// use mudssky_utils::logger::{LogLevel, LogQuery, Logger, LoggerConfig, MemoryOutput};
+/// use std::sync::Arc;
+///
+/// let output = Arc::new(MemoryOutput::default());
+/// let logger = Logger::new(LoggerConfig::new("app".to_string()).with_output(output.clone()));
+///
+/// logger.warn("disk almost full");
+/// output.assert_logged(&LogQuery::new().level(LogLevel::Warn).message_contains("disk"));
+/// ```
+#[derive(Debug)]
+pub struct MemoryOutput {
+    capacity: usize,
+    records: Mutex<VecDeque<CapturedLog>>,
+}
+
+impl Default for MemoryOutput {
+    fn default() -> Self {
+        Self::new(1000)
+    }
+}
+
+impl MemoryOutput {
+    /// Create a memory output keeping at most `capacity` records
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            records: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    /// Get the captured entries, oldest first
+    pub fn entries(&self) -> Vec<LogEntry> {
+        let records = self.records.lock().unwrap();
+        records.iter().filter_map(|r| r.entry.clone()).collect()
+    }
+
+    /// Get the formatted lines, oldest first
+    pub fn formatted(&self) -> Vec<String> {
+        let records = self.records.lock().unwrap();
+        records.iter().map(|r| r.formatted.clone()).collect()
+    }
+
+    /// Get the messages of the captured entries
+    pub fn messages(&self) -> Vec<String> {
+        self.entries().into_iter().map(|e| e.message).collect()
+    }
+
+    /// Number of captured records
+    pub fn len(&self) -> usize {
+        self.records.lock().unwrap().len()
+    }
+
+    /// Check if nothing was captured
+    pub fn is_empty(&self) -> bool {
+        self.records.lock().unwrap().is_empty()
+    }
+
+    /// Remove all captured records
+    pub fn clear(&self) {
+        self.records.lock().unwrap().clear();
+    }
+
+    /// Get the entries matching a query
+    pub fn query(&self, query: &LogQuery) -> Vec<LogEntry> {
+        query.apply(&self.entries())
+    }
+
+    /// Get the entries with exactly this level
+    pub fn by_level(&self, level: LogLevel) -> Vec<LogEntry> {
+        self.query(&LogQuery::new().level(level))
+    }
+
+    /// Get the entries from a logger
+    pub fn by_logger(&self, name: &str) -> Vec<LogEntry> {
+        self.query(&LogQuery::new().logger(name))
+    }
+
+    /// Get the entries whose message contains `text`
+    pub fn containing(&self, text: &str) -> Vec<LogEntry> {
+        self.query(&LogQuery::new().message_contains(text))
+    }
+
+    /// Get the entries that carry a metadata key
+    pub fn with_metadata_key(&self, key: &str) -> Vec<LogEntry> {
+        self.query(&LogQuery::new().has_metadata(key))
+    }
+
+    /// Panic unless at least one entry matches
+    #[track_caller]
+    pub fn assert_logged(&self, query: &LogQuery) {
+        if self.query(query).is_empty() {
+            panic!(
+                "expected an entry matching `{query}`, captured:\n{}",
+                self.describe()
+            );
+        }
+    }
+
+    /// Panic if any entry matches
+    #[track_caller]
+    pub fn assert_not_logged(&self, query: &LogQuery) {
+        let matched = self.query(query);
+        if !matched.is_empty() {
+            panic!(
+                "expected no entry matching `{query}`, found {}:\n{}",
+                matched.len(),
+                self.describe()
+            );
+        }
+    }
+
+    /// Panic unless exactly `count` entries match
+    #[track_caller]
+    pub fn assert_count(&self, query: &LogQuery, count: usize) {
+        let matched = self.query(query).len();
+        if matched != count {
+            panic!(
+                "expected {count} entries matching `{query}`, found {matched}:\n{}",
+                self.describe()
+            );
+        }
+    }
+
+    fn describe(&self) -> String {
+        let formatted = self.formatted();
+        if formatted.is_empty() {
+            return "  <nothing>".to_string();
+        }
+        formatted.iter().map(|line| format!("  {line}")).collect::<Vec<_>>().join("\n")
+    }
+
+    fn push(&self, record: CapturedLog) {
+        let mut records = self.records.lock().unwrap();
+        if records.len() >= self.capacity {
+            records.pop_front();
+        }
+        records.push_back(record);
+    }
+}
+
+impl LogOutput for MemoryOutput {
+    fn write(&self, formatted_message: &str) {
+        self.push(CapturedLog {
+            entry: None,
+            formatted: formatted_message.to_string(),
+        });
+    }
+
+    fn write_entry(&self, entry: &LogEntry, formatted_message: &str) {
+        self.push(CapturedLog {
+            entry: Some(entry.clone()),
+            formatted: formatted_message.to_string(),
+        });
+    }
+}
@@ -0,0 +1,111 @@
+//! Fan-out output with per-sink thresholds and formatters
+
+use super::{LogEntry, LogFormatter, LogLevel, LogOutput};
+use std::sync::Arc;
+
+/// One destination of a [`MultiOutput`]
+#[derive(Clone)]
+pub struct OutputSink {
+    pub min_level: LogLevel,
+    /// Formatter for this sink; `None` reuses the logger's formatted line
+    pub formatter: Option<Arc<dyn LogFormatter>>,
+    pub output: Arc<dyn LogOutput>,
+}
+
+impl std::fmt::Debug for OutputSink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OutputSink")
+            .field("min_level", &self.min_level)
+            .field("formatter", &self.formatter.as_ref().map(|_| "<formatter>"))
+            .field("output", &"<output>")
+            .finish()
+    }
+}
+
+/// Output that tees entries to several sinks
+///
+/// Each sink only receives entries at or above its own `min_level` and can
+/// format them with its own formatter. The logger level still applies first,
+/// so set it no higher than the lowest sink level.
+///
+/// # Examples
+///
+/// ```
+/// use mudssky_utils::logger::{
+///     ConsoleOutput, JsonFormatter, LogLevel, LoggerConfig, MemoryOutput, MultiOutput,
+/// };
+/// use std::sync::Arc;
+///
+/// let errors = Arc::new(MemoryOutput::default());
+/// let output = MultiOutput::new()
+///     .with_sink(Arc::new(ConsoleOutput), LogLevel::Debug)
+///     .with_formatted_sink(errors.clone(), LogLevel::Error, Arc::new(JsonFormatter));
+/// let config = LoggerConfig::new("app".to_string())
+///     .with_level(LogLevel::Debug)
+///     .with_output(Arc::new(output));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MultiOutput {
+    sinks: Vec<OutputSink>,
+}
+
+impl MultiOutput {
+    /// Create an output without sinks
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a sink that reuses the logger's formatted line
+    pub fn with_sink(mut self, output: Arc<dyn LogOutput>, min_level: LogLevel) -> Self {
+        self.sinks.push(OutputSink {
+            min_level,
+            formatter: None,
+            output,
+        });
+        self
+    }
+
+    /// Add a sink with its own formatter
+    pub fn with_formatted_sink(
+        mut self,
+        output: Arc<dyn LogOutput>,
+        min_level: LogLevel,
+        formatter: Arc<dyn LogFormatter>,
+    ) -> Self {
+        self.sinks.push(OutputSink {
+            min_level,
+            formatter: Some(formatter),
+            output,
+        });
+        self
+    }
+
+    /// Get the configured sinks
+    pub fn sinks(&self) -> &[OutputSink] {
+        &self.sinks
+    }
+}
+
+impl LogOutput for MultiOutput {
+    /// Lines without an entry have no level and go to every sink unchanged
+    fn write(&self, formatted_message: &str) {
+        for sink in &self.sinks {
+            sink.output.write(formatted_message);
+        }
+    }
+
+    fn write_entry(&self, entry: &LogEntry, formatted_message: &str) {
+        for sink in self.sinks.iter().filter(|s| entry.level >= s.min_level) {
+            match &sink.formatter {
+                Some(formatter) => sink.output.write_entry(entry, &formatter.format(entry)),
+                None => sink.output.write_entry(entry, formatted_message),
+            }
+        }
+    }
+
+    fn flush(&self) {
+        for sink in &self.sinks {
+            sink.output.flush();
+        }
+    }
+}
@@ -0,0 +1,262 @@
+//! Layout-string based formatter
+
+use super::{LogEntry, LogFormatter};
+use crate::error::ParseError;
+use chrono::format::{Item, StrftimeItems};
+
+const DEFAULT_DATE_FORMAT: &str = "%Y-%m-%d %H:%M:%S%.3f";
+
+/// Largest width a modifier may request
+const MAX_WIDTH: usize = 1024;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Field {
+    Timestamp(String),
+    Level,
+    LoggerName,
+    Message,
+    Metadata,
+    ThreadName,
+    ThreadId,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct Modifier {
+    left_align: bool,
+    min_width: Option<usize>,
+    max_width: Option<usize>,
+}
+
+impl Modifier {
+    fn apply(&self, value: String) -> String {
+        let value = match self.max_width {
+            Some(max) if value.chars().count() > max => value.chars().take(max).collect(),
+            _ => value,
+        };
+        match self.min_width {
+            Some(min) if self.left_align => format!("{value:<min$}"),
+            Some(min) => format!("{value:>min$}"),
+            None => value,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Literal(String),
+    Field(Field, Modifier),
+}
+
+/// Formatter driven by a layout pattern
+///
+/// Supported tokens:
+///
+/// | Token | Output |
+/// |-------|--------|
+/// | `%d` / `%d{fmt}` | timestamp, `fmt` is a chrono format (default `%Y-%m-%d %H:%M:%S%.3f`) |
+/// | `%l` | level |
+/// | `%n` | logger name |
+/// | `%m` | message |
+/// | `%M` | metadata as JSON, empty when there is none |
+/// | `%t` | thread name (`unnamed` when not set) |
+/// | `%T` | thread id |
+/// | `%%` | a literal `%` |
+///
+/// A token may carry a modifier between `%` and the letter: `-` left-aligns,
+/// a number sets the minimum width and `.N` truncates to the first `N`
+/// characters, e.g. `%-5l` or `%.10n`. Widths are limited to 1024.
+#[derive(Debug, Clone)]
+pub struct PatternFormatter {
+    pattern: String,
+    segments: Vec<Segment>,
+}
+
+impl PatternFormatter {
+    /// Create a formatter from a layout pattern
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use mudssky_utils::logger::{LogEntry, LogFormatter, LogLevel, PatternFormatter};
+    ///
+    /// let formatter = PatternFormatter::new("[%-5l] %n - %m").unwrap();
+    /// let entry = LogEntry::new(LogLevel::Info, "app".to_string(), "started".to_string());
+    /// assert_eq!(formatter.format(&entry), "[INFO ] app - started");
+    ///
+    /// assert!(PatternFormatter::new("%q").is_err());
+    /// ```
+    pub fn new(pattern: &str) -> Result<Self, ParseError> {
+        Ok(Self {
+            pattern: pattern.to_string(),
+            segments: parse_pattern(pattern)?,
+        })
+    }
+
+    /// Get the layout pattern
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+}
+
+impl LogFormatter for PatternFormatter {
+    fn format(&self, entry: &LogEntry) -> String {
+        let mut output = String::new();
+        for segment in &self.segments {
+            match segment {
+                Segment::Literal(text) => output.push_str(text),
+                Segment::Field(field, modifier) => {
+                    output.push_str(&modifier.apply(render_field(field, entry)));
+                }
+            }
+        }
+        output
+    }
+}
+
+fn render_field(field: &Field, entry: &LogEntry) -> String {
+    match field {
+        Field::Timestamp(format) => entry.timestamp.format(format).to_string(),
+        Field::Level => entry.level.to_string(),
+        Field::LoggerName => entry.logger_name.clone(),
+        Field::Message => entry.message.clone(),
+        Field::Metadata => {
+            if entry.metadata.is_empty() {
+                String::new()
+            } else {
+                serde_json::to_string(&entry.metadata).unwrap_or_default()
+            }
+        }
+        Field::ThreadName => std::thread::current().name().unwrap_or("unnamed").to_string(),
+        Field::ThreadId => {
+            // `ThreadId` only exposes its number through `Debug`: "ThreadId(N)"
+            let id = format!("{:?}", std::thread::current().id());
+            id.trim_start_matches("ThreadId(").trim_end_matches(')').to_string()
+        }
+    }
+}
+
+fn parse_pattern(pattern: &str) -> Result<Vec<Segment>, ParseError> {
+    let chars: Vec<(usize, char)> = pattern.char_indices().collect();
+    let mut segments = Vec::new();
+    let mut literal = String::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let (start, c) = chars[i];
+        if c != '%' {
+            literal.push(c);
+            i += 1;
+            continue;
+        }
+
+        i += 1;
+        if matches!(chars.get(i), Some((_, '%'))) {
+            literal.push('%');
+            i += 1;
+            continue;
+        }
+
+        let mut modifier = Modifier::default();
+        if matches!(chars.get(i), Some((_, '-'))) {
+            modifier.left_align = true;
+            i += 1;
+        }
+        modifier.min_width = read_number(pattern, &chars, &mut i)?;
+        if matches!(chars.get(i), Some((_, '.'))) {
+            i += 1;
+            modifier.max_width = read_number(pattern, &chars, &mut i)?;
+            if modifier.max_width.is_none() {
+                return Err(ParseError::with_position(
+                    pattern,
+                    "truncation width after '.'",
+                    start,
+                ));
+            }
+        }
+
+        let field = match chars.get(i).map(|(_, c)| *c) {
+            Some('d') => {
+                i += 1;
+                let format = if matches!(chars.get(i), Some((_, '{'))) {
+                    let close = chars[i..]
+                        .iter()
+                        .position(|(_, c)| *c == '}')
+                        .map(|offset| i + offset)
+                        .ok_or_else(|| {
+                            ParseError::with_position(pattern, "closing '}' for date format", start)
+                        })?;
+                    let format: String = chars[i + 1..close].iter().map(|(_, c)| c).collect();
+                    i = close + 1;
+                    format
+                } else {
+                    DEFAULT_DATE_FORMAT.to_string()
+                };
+                if StrftimeItems::new(&format).any(|item| matches!(item, Item::Error)) {
+                    return Err(ParseError::with_position(
+                        pattern,
+                        "valid chrono date format",
+                        start,
+                    ));
+                }
+                Field::Timestamp(format)
+            }
+            Some(token) => {
+                i += 1;
+                match token {
+                    'l' => Field::Level,
+                    'n' => Field::LoggerName,
+                    'm' => Field::Message,
+                    'M' => Field::Metadata,
+                    't' => Field::ThreadName,
+                    'T' => Field::ThreadId,
+                    _ => {
+                        return Err(ParseError::with_position(
+                            pattern,
+                            "one of %d %l %n %m %M %t %T %%",
+                            start,
+                        ));
+                    }
+                }
+            }
+            None => {
+                return Err(ParseError::with_position(pattern, "token after '%'", start));
+            }
+        };
+
+        if !literal.is_empty() {
+            segments.push(Segment::Literal(std::mem::take(&mut literal)));
+        }
+        segments.push(Segment::Field(field, modifier));
+    }
+
+    if !literal.is_empty() {
+        segments.push(Segment::Literal(literal));
+    }
+    Ok(segments)
+}
+
+/// Read a width, rejecting values above [`MAX_WIDTH`]
+fn read_number(
+    pattern: &str,
+    chars: &[(usize, char)],
+    i: &mut usize,
+) -> Result<Option<usize>, ParseError> {
+    let start = chars.get(*i).map_or(pattern.len(), |(position, _)| *position);
+    let mut value: Option<usize> = None;
+    while let Some((_, c)) = chars.get(*i) {
+        let Some(digit) = c.to_digit(10) else {
+            break;
+        };
+        let width = value
+            .unwrap_or(0)
+            .checked_mul(10)
+            .and_then(|v| v.checked_add(digit as usize))
+            .filter(|&v| v <= MAX_WIDTH)
+            .ok_or_else(|| {
+                ParseError::with_position(pattern, format!("width of at most {MAX_WIDTH}"), start)
+            })?;
+        value = Some(width);
+        *i += 1;
+    }
+    Ok(value)
+}
@@ -0,0 +1,191 @@
+//! Entry queries shared by in-memory capture and log readers
+
+use super::{LogEntry, LogLevel};
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use std::fmt;
+use std::sync::Arc;
+
+type PredicateFn = dyn Fn(&LogEntry) -> bool + Send + Sync;
+
+/// Custom condition added with [`LogQuery::filter`]
+#[derive(Clone)]
+struct Predicate {
+    description: String,
+    test: Arc<PredicateFn>,
+}
+
+impl fmt::Debug for Predicate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Predicate").field(&self.description).finish()
+    }
+}
+
+/// Composable filter over [`LogEntry`] values
+///
+/// All conditions must hold for an entry to match.
+///
+/// # Examples
+///
+/// ```
+/// use mudssky_utils::logger::{LogEntry, LogLevel, LogQuery};
+/// use serde_json::json;
+///
+/// let entry = LogEntry::new(LogLevel::Warn, "db".to_string(), "slow query".to_string())
+///     .with_metadata("ms".to_string(), json!(250));
+/// let query = LogQuery::new().min_level(LogLevel::Warn).message_contains("slow").has_metadata("ms");
+/// assert!(query.matches(&entry));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct LogQuery {
+    level: Option<LogLevel>,
+    min_level: Option<LogLevel>,
+    logger: Option<String>,
+    message: Option<String>,
+    metadata_keys: Vec<String>,
+    metadata_values: Vec<(String, Value)>,
+    since: Option<DateTime<Utc>>,
+    until: Option<DateTime<Utc>>,
+    predicates: Vec<Predicate>,
+}
+
+impl LogQuery {
+    /// Create a query that matches every entry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Match entries with exactly this level
+    pub fn level(mut self, level: LogLevel) -> Self {
+        self.level = Some(level);
+        self
+    }
+
+    /// Match entries at or above this level
+    pub fn min_level(mut self, level: LogLevel) -> Self {
+        self.min_level = Some(level);
+        self
+    }
+
+    /// Match entries from this logger name
+    pub fn logger(mut self, name: impl Into<String>) -> Self {
+        self.logger = Some(name.into());
+        self
+    }
+
+    /// Match entries whose message contains the given text
+    pub fn message_contains(mut self, text: impl Into<String>) -> Self {
+        self.message = Some(text.into());
+        self
+    }
+
+    /// Match entries that have a metadata key
+    pub fn has_metadata(mut self, key: impl Into<String>) -> Self {
+        self.metadata_keys.push(key.into());
+        self
+    }
+
+    /// Match entries whose metadata key has the given value
+    pub fn metadata_eq(mut self, key: impl Into<String>, value: Value) -> Self {
+        self.metadata_values.push((key.into(), value));
+        self
+    }
+
+    /// Match entries whose metadata value for `key` satisfies a predicate
+    ///
+    /// Entries without the key do not match.
+    pub fn metadata_matches<F>(mut self, key: impl Into<String>, predicate: F) -> Self
+    where
+        F: Fn(&Value) -> bool + Send + Sync + 'static,
+    {
+        let key = key.into();
+        self.predicates.push(Predicate {
+            description: format!("metadata {key:?} matches <predicate>"),
+            test: Arc::new(move |entry| entry.metadata.get(&key).is_some_and(&predicate)),
+        });
+        self
+    }
+
+    /// Match entries logged at or after `time`
+    pub fn since(mut self, time: DateTime<Utc>) -> Self {
+        self.since = Some(time);
+        self
+    }
+
+    /// Match entries logged before `time`
+    pub fn until(mut self, time: DateTime<Utc>) -> Self {
+        self.until = Some(time);
+        self
+    }
+
+    /// Match entries for which a custom predicate returns `true`
+    pub fn filter<F>(mut self, predicate: F) -> Self
+    where
+        F: Fn(&LogEntry) -> bool + Send + Sync + 'static,
+    {
+        self.predicates.push(Predicate {
+            description: "<predicate>".to_string(),
+            test: Arc::new(predicate),
+        });
+        self
+    }
+
+    /// Check whether an entry satisfies the query
+    pub fn matches(&self, entry: &LogEntry) -> bool {
+        self.level.is_none_or(|level| entry.level == level)
+            && self.min_level.is_none_or(|level| entry.level >= level)
+            && self.logger.as_ref().is_none_or(|name| &entry.logger_name == name)
+            && self.message.as_ref().is_none_or(|text| entry.message.contains(text.as_str()))
+            && self.metadata_keys.iter().all(|key| entry.metadata.contains_key(key))
+            && self
+                .metadata_values
+                .iter()
+                .all(|(key, value)| entry.metadata.get(key) == Some(value))
+            && self.since.is_none_or(|time| entry.timestamp >= time)
+            && self.until.is_none_or(|time| entry.timestamp < time)
+            && self.predicates.iter().all(|p| (p.test)(entry))
+    }
+
+    /// Keep the entries that satisfy the query
+    pub fn apply<'a>(&self, entries: impl IntoIterator<Item = &'a LogEntry>) -> Vec<LogEntry> {
+        entries.into_iter().filter(|e| self.matches(e)).cloned().collect()
+    }
+}
+
+impl fmt::Display for LogQuery {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = Vec::new();
+        if let Some(level) = self.level {
+            parts.push(format!("level == {level}"));
+        }
+        if let Some(level) = self.min_level {
+            parts.push(format!("level >= {level}"));
+        }
+        if let Some(name) = &self.logger {
+            parts.push(format!("logger == {name:?}"));
+        }
+        if let Some(text) = &self.message {
+            parts.push(format!("message contains {text:?}"));
+        }
+        for key in &self.metadata_keys {
+            parts.push(format!("has metadata {key:?}"));
+        }
+        for (key, value) in &self.metadata_values {
+            parts.push(format!("metadata {key:?} == {value}"));
+        }
+        if let Some(time) = self.since {
+            parts.push(format!("timestamp >= {}", time.to_rfc3339()));
+        }
+        if let Some(time) = self.until {
+            parts.push(format!("timestamp < {}", time.to_rfc3339()));
+        }
+        for predicate in &self.predicates {
+            parts.push(predicate.description.clone());
+        }
+        if parts.is_empty() {
+            write!(f, "<any entry>")
+        } else {
+            write!(f, "{}", parts.join(" && "))
+        }
+    }
+}
@@ -0,0 +1,357 @@
+//! Reading JSON log files produced by `JsonFormatter`
+
+use super::{LogEntry, LogLevel, LogQuery};
+use crate::error::ParseError;
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Parse one line written by [`JsonFormatter`](super::JsonFormatter)
+///
+/// `timestamp`, `level`, `logger` and `message` fill the entry fields, every
+/// other key becomes metadata.
+///
+/// # Examples
+///
+/// ```
+/// use mudssky_utils::logger::{LogLevel, parse_json_line};
+///
+/// let line = r#"{"timestamp":"2024-01-02T03:04:05Z","level":"WARN","logger":"db","message":"slow","ms":250}"#;
+/// let entry = parse_json_line(line).unwrap();
+/// assert_eq!(entry.level, LogLevel::Warn);
+/// assert_eq!(entry.metadata["ms"], 250);
+/// ```
+pub fn parse_json_line(line: &str) -> Result<LogEntry, ParseError> {
+    let mut object = match serde_json::from_str::<Value>(line) {
+        Ok(Value::Object(object)) => object,
+        _ => return Err(ParseError::new(line, "JSON log object")),
+    };
+
+    let mut take_str = |field: &str| match object.remove(field) {
+        Some(Value::String(value)) => Ok(value),
+        _ => Err(ParseError::new(line, format!("string field `{field}`"))),
+    };
+
+    let timestamp = take_str("timestamp")?;
+    let timestamp = DateTime::parse_from_rfc3339(&timestamp)
+        .map_err(|_| ParseError::new(timestamp.as_str(), "RFC 3339 timestamp"))?
+        .with_timezone(&Utc);
+    let level = take_str("level")?;
+    let level = level
+        .parse::<LogLevel>()
+        .map_err(|_| ParseError::new(level.as_str(), "log level"))?;
+    let logger_name = take_str("logger")?;
+    let message = take_str("message")?;
+
+    Ok(LogEntry {
+        timestamp,
+        level,
+        logger_name,
+        message,
+        metadata: object.into_iter().collect(),
+    })
+}
+
+/// Iterator over the entries of a JSON log
+///
+/// Blank lines are skipped; malformed lines, including invalid UTF-8, are
+/// skipped and counted. Iteration stops at the first I/O error, which is kept
+/// in [`LogReader::io_error`].
+///
+/// # Examples
+///
+/// ```
+/// use mudssky_utils::logger::{LogLevel, LogQuery, LogReader};
+///
+/// let log = concat!(
+///     r#"{"timestamp":"2024-01-02T03:04:05Z","level":"INFO","logger":"app","message":"up"}"#, "\n",
+///     "not json\n",
+///     r#"{"timestamp":"2024-01-02T03:04:06Z","level":"ERROR","logger":"db","message":"down"}"#, "\n",
+/// );
+/// let mut reader = LogReader::new(log.as_bytes())
+///     .with_query(LogQuery::new().min_level(LogLevel::Error));
+/// let entries: Vec<_> = reader.by_ref().collect();
+/// assert_eq!(entries.len(), 1);
+/// assert_eq!(reader.malformed_count(), 1);
+/// ```
+#[derive(Debug)]
+pub struct LogReader<R> {
+    reader: R,
+    query: LogQuery,
+    line: Vec<u8>,
+    line_number: usize,
+    malformed: usize,
+    io_error: Option<io::Error>,
+}
+
+impl LogReader<BufReader<File>> {
+    /// Open a log file for reading
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        Ok(Self::new(BufReader::new(File::open(path)?)))
+    }
+}
+
+impl<R: BufRead> LogReader<R> {
+    /// Read entries from any buffered reader
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            query: LogQuery::new(),
+            line: Vec::new(),
+            line_number: 0,
+            malformed: 0,
+            io_error: None,
+        }
+    }
+
+    /// Only yield entries that match a query
+    pub fn with_query(mut self, query: LogQuery) -> Self {
+        self.query = query;
+        self
+    }
+
+    /// Number of lines read so far
+    pub fn line_number(&self) -> usize {
+        self.line_number
+    }
+
+    /// Number of non-blank lines that could not be parsed
+    pub fn malformed_count(&self) -> usize {
+        self.malformed
+    }
+
+    /// The I/O error that ended iteration, if any
+    pub fn io_error(&self) -> Option<&io::Error> {
+        self.io_error.as_ref()
+    }
+}
+
+impl<R: BufRead> Iterator for LogReader<R> {
+    type Item = LogEntry;
+
+    fn next(&mut self) -> Option<LogEntry> {
+        if self.io_error.is_some() {
+            return None;
+        }
+        loop {
+            self.line.clear();
+            match self.reader.read_until(b'\n', &mut self.line) {
+                Ok(0) => return None,
+                Ok(_) => self.line_number += 1,
+                Err(e) => {
+                    self.io_error = Some(e);
+                    return None;
+                }
+            }
+
+            let Ok(line) = std::str::from_utf8(&self.line) else {
+                self.malformed += 1;
+                continue;
+            };
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            match parse_json_line(line) {
+                Ok(entry) if self.query.matches(&entry) => return Some(entry),
+                Ok(_) => {}
+                Err(_) => self.malformed += 1,
+            }
+        }
+    }
+}
+
+/// Read every matching entry of a JSON log file
+pub fn read_json_log(path: impl AsRef<Path>, query: &LogQuery) -> io::Result<Vec<LogEntry>> {
+    let mut reader = LogReader::open(path)?.with_query(query.clone());
+    let entries = reader.by_ref().collect();
+    match reader.io_error {
+        Some(e) => Err(e),
+        None => Ok(entries),
+    }
+}
+
+/// Follows a JSON log file as it grows, across rotation and truncation
+///
+/// When the file at the path is replaced (e.g. by
+/// [`FileOutput`](super::FileOutput) rotation), the rest of the old file is
+/// read before switching to the new one.
+#[derive(Debug)]
+pub struct LogTail {
+    path: PathBuf,
+    file: Option<File>,
+    identity: Option<FileIdentity>,
+    offset: u64,
+    partial: Vec<u8>,
+    query: LogQuery,
+    malformed: usize,
+}
+
+impl LogTail {
+    /// Follow a file starting at its current end
+    ///
+    /// A missing file is waited for and then read from the beginning.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let mut tail = Self::from_start(path)?;
+        if let Some(file) = tail.file.as_mut() {
+            tail.offset = file.seek(SeekFrom::End(0))?;
+        }
+        Ok(tail)
+    }
+
+    /// Follow a file starting at its beginning
+    pub fn from_start(path: impl AsRef<Path>) -> io::Result<Self> {
+        let mut tail = Self {
+            path: path.as_ref().to_path_buf(),
+            file: None,
+            identity: None,
+            offset: 0,
+            partial: Vec::new(),
+            query: LogQuery::new(),
+            malformed: 0,
+        };
+        tail.reopen()?;
+        Ok(tail)
+    }
+
+    /// Only return entries that match a query
+    pub fn with_query(mut self, query: LogQuery) -> Self {
+        self.query = query;
+        self
+    }
+
+    /// Number of complete lines that could not be parsed
+    pub fn malformed_count(&self) -> usize {
+        self.malformed
+    }
+
+    /// Read the entries appended since the last call
+    ///
+    /// A trailing line without a newline is kept until it is completed.
+    pub fn poll(&mut self) -> io::Result<Vec<LogEntry>> {
+        let mut entries = Vec::new();
+        if self.file.is_none() && !self.reopen()? {
+            return Ok(entries);
+        }
+
+        let current = fs::metadata(&self.path).ok();
+        let replaced = current
+            .as_ref()
+            .is_some_and(|meta| Some(FileIdentity::of(meta)) != self.identity);
+        let truncated = !replaced && current.as_ref().is_some_and(|meta| meta.len() < self.offset);
+
+        if truncated {
+            self.offset = 0;
+            self.partial.clear();
+            if let Some(file) = self.file.as_mut() {
+                file.seek(SeekFrom::Start(0))?;
+            }
+        }
+
+        self.read_available(&mut entries)?;
+
+        if replaced {
+            // Whatever is left of the old file will never get its newline
+            let rest = std::mem::take(&mut self.partial);
+            self.take_line(&String::from_utf8_lossy(&rest), &mut entries);
+            self.reopen()?;
+            self.read_available(&mut entries)?;
+        }
+        Ok(entries)
+    }
+
+    /// Wait until at least one matching entry is available
+    ///
+    /// The file is polled every `interval` on the tokio runtime.
+    pub async fn next_batch(&mut self, interval: Duration) -> io::Result<Vec<LogEntry>> {
+        loop {
+            let entries = self.poll()?;
+            if !entries.is_empty() {
+                return Ok(entries);
+            }
+            tokio::time::sleep(interval).await;
+        }
+    }
+}
+
+impl LogTail {
+    /// Open the file at the path from the start; returns whether it exists
+    fn reopen(&mut self) -> io::Result<bool> {
+        match File::open(&self.path) {
+            Ok(file) => {
+                self.identity = Some(FileIdentity::of(&file.metadata()?));
+                self.file = Some(file);
+                self.offset = 0;
+                self.partial.clear();
+                Ok(true)
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                self.file = None;
+                self.identity = None;
+                Ok(false)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    fn read_available(&mut self, entries: &mut Vec<LogEntry>) -> io::Result<()> {
+        let Some(file) = self.file.as_mut() else {
+            return Ok(());
+        };
+        let read = file.read_to_end(&mut self.partial)?;
+        self.offset += read as u64;
+
+        if let Some(end) = self.partial.iter().rposition(|&b| b == b'\n') {
+            let complete: Vec<u8> = self.partial.drain(..=end).collect();
+            for line in String::from_utf8_lossy(&complete).lines() {
+                self.take_line(line, entries);
+            }
+        }
+        Ok(())
+    }
+
+    fn take_line(&mut self, line: &str, entries: &mut Vec<LogEntry>) {
+        let line = line.trim();
+        if line.is_empty() {
+            return;
+        }
+        match parse_json_line(line) {
+            Ok(entry) if self.query.matches(&entry) => entries.push(entry),
+            Ok(_) => {}
+            Err(_) => self.malformed += 1,
+        }
+    }
+}
+
+/// Identifies the file behind a path so replacement can be detected
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct FileIdentity {
+    #[cfg(unix)]
+    dev: u64,
+    #[cfg(unix)]
+    ino: u64,
+    #[cfg(not(unix))]
+    created: Option<std::time::SystemTime>,
+}
+
+impl FileIdentity {
+    fn of(meta: &fs::Metadata) -> Self {
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::MetadataExt;
+            Self {
+                dev: meta.dev(),
+                ino: meta.ino(),
+            }
+        }
+        #[cfg(not(unix))]
+        {
+            Self {
+                created: meta.created().ok(),
+            }
+        }
+    }
+}
@@ -0,0 +1,168 @@
+//! Redaction of sensitive data before formatting
+
+use super::LogEntry;
+use crate::regex::{get_patterns, to_search_pattern};
+use regex::{Regex, RegexBuilder};
+use serde_json::Value;
+
+/// Default mask for redacted values
+pub const DEFAULT_REDACTION_MASK: &str = "[REDACTED]";
+
+/// Content detector used to scan messages and string values
+#[derive(Debug, Clone)]
+pub enum Detector {
+    /// Email addresses (`regex` module `email` pattern)
+    Email,
+    /// Chinese mobile numbers (`regex` module `mobile_cn` pattern)
+    MobileCn,
+    /// Credit card numbers (`regex` module `credit_card` pattern)
+    CreditCard,
+    /// Any custom pattern
+    Pattern(Regex),
+}
+
+impl Detector {
+    fn regex(&self) -> Regex {
+        let patterns = get_patterns();
+        let anchored = match self {
+            Detector::Email => &patterns.email,
+            Detector::MobileCn => &patterns.mobile_cn,
+            Detector::CreditCard => &patterns.credit_card,
+            Detector::Pattern(regex) => return regex.clone(),
+        };
+        to_search_pattern(anchored).expect("built-in patterns are valid")
+    }
+}
+
+/// Masks sensitive metadata values and message content
+///
+/// Metadata values whose key matches one of the key patterns (case-insensitive)
+/// are replaced by the key mask, including keys in nested objects. Messages and
+/// the remaining string values are scanned with the detectors and each match is
+/// replaced by the detector's mask.
+///
+/// # Examples
+///
+/// ```
+/// use mudssky_utils::logger::{LogEntry, LogLevel, Redactor};
+/// use serde_json::json;
+///
+/// let redactor = Redactor::default();
+/// let mut entry = LogEntry::new(LogLevel::Info, "api".to_string(), "mail bob@example.com".to_string())
+///     .with_metadata("token".to_string(), json!("abc123"));
+/// redactor.redact(&mut entry);
+///
+/// assert_eq!(entry.message, "mail [REDACTED]");
+/// assert_eq!(entry.metadata["token"], json!("[REDACTED]"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Redactor {
+    key_patterns: Vec<Regex>,
+    key_mask: String,
+    detectors: Vec<(Regex, String)>,
+}
+
+impl Default for Redactor {
+    /// Masks `password`, `secret`, `token`, `authorization`, `api_key` and
+    /// `cookie` keys and detects emails, Chinese mobile numbers and credit cards
+    ///
+    /// Key names only match whole segments of a key, delimited by punctuation
+    /// or a camelCase boundary, so `db_password` and `sessionCookie` are masked
+    /// but `bypass` and `compass` are not. `token` must be the last segment,
+    /// which leaves counters such as `token_count` and `max_tokens` readable.
+    fn default() -> Self {
+        let mut redactor = Self::new();
+        for (name, camel, end) in [
+            ("pass(word|wd)?", "Pass(word|wd)?", "($|[^a-z0-9])"),
+            ("secret", "Secret", "($|[^a-z0-9])"),
+            ("token", "Token", "$"),
+            ("authorization", "Authorization", "($|[^a-z0-9])"),
+            ("api[_-]?key", "Api[_-]?[Kk]ey", "($|[^a-z0-9])"),
+            ("cookie", "Cookie", "($|[^a-z0-9])"),
+        ] {
+            let pattern = format!("(^|[^a-z0-9]){name}{end}|(?-i:[a-z0-9]{camel}{end})");
+            redactor = redactor.with_key_pattern(&pattern).expect("built-in patterns are valid");
+        }
+        redactor
+            .with_detector(Detector::Email, DEFAULT_REDACTION_MASK)
+            .with_detector(Detector::MobileCn, DEFAULT_REDACTION_MASK)
+            .with_detector(Detector::CreditCard, DEFAULT_REDACTION_MASK)
+    }
+}
+
+impl Redactor {
+    /// Create a redactor without any rules
+    pub fn new() -> Self {
+        Self {
+            key_patterns: Vec::new(),
+            key_mask: DEFAULT_REDACTION_MASK.to_string(),
+            detectors: Vec::new(),
+        }
+    }
+
+    /// Mask metadata values whose key matches `pattern` (case-insensitive regex)
+    pub fn with_key_pattern(mut self, pattern: &str) -> Result<Self, regex::Error> {
+        let regex = RegexBuilder::new(pattern).case_insensitive(true).build()?;
+        self.key_patterns.push(regex);
+        Ok(self)
+    }
+
+    /// Set the mask used for sensitive keys
+    pub fn with_key_mask(mut self, mask: impl Into<String>) -> Self {
+        self.key_mask = mask.into();
+        self
+    }
+
+    /// Replace content found by `detector` with `mask`
+    ///
+    /// The mask may reference capture groups of custom patterns (`$1`).
+    pub fn with_detector(mut self, detector: Detector, mask: impl Into<String>) -> Self {
+        self.detectors.push((detector.regex(), mask.into()));
+        self
+    }
+
+    /// Check whether a metadata key is considered sensitive
+    pub fn is_sensitive_key(&self, key: &str) -> bool {
+        self.key_patterns.iter().any(|re| re.is_match(key))
+    }
+
+    /// Apply the detectors to a piece of text
+    pub fn redact_text(&self, text: &str) -> String {
+        let mut result = text.to_string();
+        for (regex, mask) in &self.detectors {
+            if regex.is_match(&result) {
+                result = regex.replace_all(&result, mask.as_str()).to_string();
+            }
+        }
+        result
+    }
+
+    /// Redact the message and metadata of an entry in place
+    pub fn redact(&self, entry: &mut LogEntry) {
+        entry.message = self.redact_text(&entry.message);
+        for (key, value) in entry.metadata.iter_mut() {
+            self.redact_field(key, value);
+        }
+    }
+
+    fn redact_field(&self, key: &str, value: &mut Value) {
+        if self.is_sensitive_key(key) {
+            *value = Value::String(self.key_mask.clone());
+        } else {
+            self.redact_value(value);
+        }
+    }
+
+    fn redact_value(&self, value: &mut Value) {
+        match value {
+            Value::String(s) => *s = self.redact_text(s),
+            Value::Array(items) => items.iter_mut().for_each(|item| self.redact_value(item)),
+            Value::Object(map) => {
+                for (key, value) in map.iter_mut() {
+                    self.redact_field(key, value);
+                }
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,224 @@
+//! Log sampling and rate limiting
+
+use super::LogEntry;
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// How entries are grouped for sampling
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SampleKey {
+    /// Group by level and message text
+    #[default]
+    Message,
+    /// Group by the `file`/`line` call site recorded by the logging macros,
+    /// falling back to the message when no call site is known
+    CallSite,
+}
+
+/// Sampling policy: the first `first` entries per `interval`, then 1 in `thereafter`
+///
+/// `thereafter: 0` drops everything past the first `first` entries, turning the
+/// policy into a plain rate limit.
+#[derive(Debug, Clone)]
+pub struct SamplingPolicy {
+    pub interval: Duration,
+    pub first: u64,
+    pub thereafter: u64,
+    pub key: SampleKey,
+    /// Maximum number of tracked keys; expired keys are evicted beyond this
+    pub max_keys: usize,
+}
+
+impl Default for SamplingPolicy {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(1),
+            first: 10,
+            thereafter: 100,
+            key: SampleKey::Message,
+            max_keys: 1024,
+        }
+    }
+}
+
+impl SamplingPolicy {
+    /// Allow at most `count` entries per key and interval
+    pub fn rate_limit(count: u64, interval: Duration) -> Self {
+        Self {
+            interval,
+            first: count,
+            thereafter: 0,
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Debug)]
+struct KeyState {
+    window_start: Instant,
+    seen: u64,
+    suppressed: u64,
+    template: LogEntry,
+}
+
+#[derive(Debug, Default)]
+struct SamplerState {
+    keys: HashMap<String, KeyState>,
+    /// Earliest end of a window with suppressed entries, if any
+    next_summary_due: Option<Instant>,
+}
+
+/// Stateful sampler applying a [`SamplingPolicy`]
+///
+/// A summary of the entries suppressed in a window is produced by the first
+/// [`check`](LogSampler::check) after the window ends, for whichever key it
+/// belongs to. When nothing is logged any more, summaries wait for
+/// [`drain_summaries`](LogSampler::drain_summaries), i.e. `Logger::flush`.
+#[derive(Debug)]
+pub struct LogSampler {
+    policy: SamplingPolicy,
+    state: Mutex<SamplerState>,
+}
+
+/// Outcome of [`LogSampler::check`]
+#[derive(Debug)]
+pub struct SampleDecision {
+    /// Whether the entry should be written
+    pub allow: bool,
+    /// Summaries of ended windows, emitted before the entry
+    pub summaries: Vec<LogEntry>,
+}
+
+impl LogSampler {
+    /// Create a sampler
+    pub fn new(policy: SamplingPolicy) -> Self {
+        Self {
+            policy,
+            state: Mutex::new(SamplerState::default()),
+        }
+    }
+
+    /// Get the policy
+    pub fn policy(&self) -> &SamplingPolicy {
+        &self.policy
+    }
+
+    /// Decide whether an entry passes and which window summaries are due
+    pub fn check(&self, entry: &LogEntry) -> SampleDecision {
+        let key = self.key_for(entry);
+        let now = Instant::now();
+        let interval = self.policy.interval;
+        let mut sampler = self.state.lock().unwrap();
+        let summaries = self.expired_summaries(&mut sampler, now);
+        let keys = &mut sampler.keys;
+
+        if !keys.contains_key(&key) && keys.len() >= self.policy.max_keys {
+            keys.retain(|_, state| now.duration_since(state.window_start) < interval);
+        }
+
+        let state = keys.entry(key).or_insert_with(|| KeyState {
+            window_start: now,
+            seen: 0,
+            suppressed: 0,
+            template: entry.clone(),
+        });
+
+        if now.duration_since(state.window_start) >= interval {
+            state.window_start = now;
+            state.seen = 0;
+            state.suppressed = 0;
+        }
+
+        state.seen += 1;
+        let allow = if state.seen <= self.policy.first {
+            true
+        } else {
+            let past_first = state.seen - self.policy.first;
+            self.policy.thereafter > 0 && past_first % self.policy.thereafter == 0
+        };
+        if !allow {
+            state.suppressed += 1;
+            state.template = entry.clone();
+            let due = state.window_start + interval;
+            sampler.next_summary_due = Some(sampler.next_summary_due.map_or(due, |d| d.min(due)));
+        }
+
+        SampleDecision { allow, summaries }
+    }
+
+    /// Take summaries for every key with suppressed entries and reset their counts
+    pub fn drain_summaries(&self) -> Vec<LogEntry> {
+        let mut sampler = self.state.lock().unwrap();
+        sampler.next_summary_due = None;
+        sampler
+            .keys
+            .values_mut()
+            .filter_map(|state| {
+                let summary = summary_entry(state);
+                state.suppressed = 0;
+                summary
+            })
+            .collect()
+    }
+
+    /// Take summaries for every key whose window has ended
+    fn expired_summaries(&self, sampler: &mut SamplerState, now: Instant) -> Vec<LogEntry> {
+        if sampler.next_summary_due.is_none_or(|due| now < due) {
+            return Vec::new();
+        }
+
+        let interval = self.policy.interval;
+        let mut summaries = Vec::new();
+        let mut next_due: Option<Instant> = None;
+        for state in sampler.keys.values_mut() {
+            if state.suppressed == 0 {
+                continue;
+            }
+            let due = state.window_start + interval;
+            if now >= due {
+                summaries.extend(summary_entry(state));
+                state.suppressed = 0;
+            } else {
+                next_due = Some(next_due.map_or(due, |d| d.min(due)));
+            }
+        }
+        sampler.next_summary_due = next_due;
+        summaries
+    }
+
+    fn key_for(&self, entry: &LogEntry) -> String {
+        if self.policy.key == SampleKey::CallSite {
+            if let (Some(file), Some(line)) =
+                (entry.metadata.get("file"), entry.metadata.get("line"))
+            {
+                return format!("{}:{}", value_text(file), value_text(line));
+            }
+        }
+        format!("{}|{}", entry.level, entry.message)
+    }
+}
+
+fn value_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn summary_entry(state: &KeyState) -> Option<LogEntry> {
+    if state.suppressed == 0 {
+        return None;
+    }
+    let template = &state.template;
+    Some(
+        LogEntry::new(
+            template.level,
+            template.logger_name.clone(),
+            format!("suppressed {} similar log entries", state.suppressed),
+        )
+        .with_metadata("suppressed".to_string(), json!(state.suppressed))
+        .with_metadata("sampled_message".to_string(), json!(template.message)),
+    )
+}
@@ -0,0 +1,329 @@
+//! Output that ships log lines to a local collector over a socket
+
+use super::LogOutput;
+use std::collections::VecDeque;
+use std::io::{self, Write};
+use std::net::{TcpStream, ToSocketAddrs, UdpSocket};
+#[cfg(unix)]
+use std::os::unix::net::UnixStream;
+#[cfg(unix)]
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Where a [`SocketOutput`] sends its lines
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SocketTarget {
+    /// One datagram per message, e.g. `127.0.0.1:514`
+    Udp(String),
+    /// Stream connection, e.g. `127.0.0.1:5170`
+    Tcp(String),
+    /// Unix domain stream socket
+    #[cfg(unix)]
+    Unix(PathBuf),
+}
+
+/// How messages are delimited on stream connections
+///
+/// UDP sends exactly one message per datagram and ignores the framing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Framing {
+    /// Append `\n` to every message
+    #[default]
+    Newline,
+    /// Prefix every message with its byte length and a space (RFC 6587)
+    OctetCounting,
+}
+
+impl Framing {
+    /// Frame a single message
+    pub fn frame(&self, message: &str) -> Vec<u8> {
+        match self {
+            Framing::Newline => {
+                let mut bytes = Vec::with_capacity(message.len() + 1);
+                bytes.extend_from_slice(message.as_bytes());
+                bytes.push(b'\n');
+                bytes
+            }
+            Framing::OctetCounting => format!("{} {}", message.len(), message).into_bytes(),
+        }
+    }
+}
+
+/// Socket output options
+#[derive(Debug, Clone)]
+pub struct SocketOutputOptions {
+    /// Message framing on stream connections
+    pub framing: Framing,
+    /// Maximum number of messages kept while the collector is unreachable
+    ///
+    /// The oldest message is dropped when the buffer is full.
+    pub buffer_capacity: usize,
+    /// Minimum time between two connection attempts
+    pub reconnect_delay: Duration,
+    /// Timeout for establishing a connection
+    pub connect_timeout: Duration,
+    /// Timeout for a single write before the connection is considered broken
+    pub write_timeout: Duration,
+}
+
+impl Default for SocketOutputOptions {
+    fn default() -> Self {
+        Self {
+            framing: Framing::Newline,
+            buffer_capacity: 1024,
+            reconnect_delay: Duration::from_secs(1),
+            connect_timeout: Duration::from_secs(1),
+            write_timeout: Duration::from_secs(5),
+        }
+    }
+}
+
+#[derive(Debug)]
+enum Connection {
+    Udp(UdpSocket),
+    Tcp(TcpStream),
+    #[cfg(unix)]
+    Unix(UnixStream),
+}
+
+impl Connection {
+    /// Send one message; on failure also returns how many bytes already went out
+    fn send(&mut self, message: &str, framing: Framing) -> Result<(), (usize, io::Error)> {
+        match self {
+            Connection::Udp(socket) => {
+                socket.send(message.as_bytes()).map(|_| ()).map_err(|e| (0, e))
+            }
+            Connection::Tcp(stream) => write_frame(stream, &framing.frame(message)),
+            #[cfg(unix)]
+            Connection::Unix(stream) => write_frame(stream, &framing.frame(message)),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Connection::Udp(_) => Ok(()),
+            Connection::Tcp(stream) => stream.flush(),
+            #[cfg(unix)]
+            Connection::Unix(stream) => stream.flush(),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct SocketState {
+    connection: Option<Connection>,
+    buffer: VecDeque<String>,
+    last_attempt: Option<Instant>,
+    dropped: u64,
+}
+
+/// Output that streams formatted lines to a collector agent
+///
+/// The connection is opened lazily and re-established after errors, at most
+/// once per [`SocketOutputOptions::reconnect_delay`]. Messages that cannot be
+/// sent are kept in a bounded buffer and retried on the next write or flush.
+/// A message that was only partly sent when the connection broke is dropped
+/// rather than sent again, so the collector never sees it twice.
+///
+/// Connecting and sending happen on the logging thread, so a slow or
+/// unreachable collector can block callers for up to `connect_timeout` plus
+/// `write_timeout`. Wrap it in an [`AsyncOutput`](super::AsyncOutput) to keep
+/// that off the callers' threads.
+#[derive(Debug)]
+pub struct SocketOutput {
+    target: SocketTarget,
+    options: SocketOutputOptions,
+    state: Mutex<SocketState>,
+}
+
+impl SocketOutput {
+    /// Create a socket output with default options
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use mudssky_utils::logger::{
+    ///     AsyncOutput, JsonFormatter, LoggerConfig, SocketOutput, SocketTarget,
+    /// };
+    /// use std::sync::Arc;
+    ///
+    /// let output = SocketOutput::new(SocketTarget::Tcp("127.0.0.1:5170".to_string()));
+    /// let output = Arc::new(AsyncOutput::new(Arc::new(output)));
+    /// let config = LoggerConfig::new("app".to_string())
+    ///     .with_formatter(Arc::new(JsonFormatter))
+    ///     .with_output(output);
+    /// ```
+    pub fn new(target: SocketTarget) -> Self {
+        Self::with_options(target, SocketOutputOptions::default())
+    }
+
+    /// Create a socket output with custom options
+    pub fn with_options(target: SocketTarget, options: SocketOutputOptions) -> Self {
+        Self {
+            target,
+            options,
+            state: Mutex::new(SocketState::default()),
+        }
+    }
+
+    /// Create a UDP output with default options
+    pub fn udp(addr: impl Into<String>) -> Self {
+        Self::new(SocketTarget::Udp(addr.into()))
+    }
+
+    /// Create a TCP output with default options
+    pub fn tcp(addr: impl Into<String>) -> Self {
+        Self::new(SocketTarget::Tcp(addr.into()))
+    }
+
+    /// Create a Unix domain socket output with default options
+    #[cfg(unix)]
+    pub fn unix(path: impl Into<PathBuf>) -> Self {
+        Self::new(SocketTarget::Unix(path.into()))
+    }
+
+    /// Get the target this output sends to
+    pub fn target(&self) -> &SocketTarget {
+        &self.target
+    }
+
+    /// Check whether a connection is currently open
+    pub fn is_connected(&self) -> bool {
+        self.state.lock().unwrap().connection.is_some()
+    }
+
+    /// Number of messages waiting for the collector
+    pub fn pending(&self) -> usize {
+        self.state.lock().unwrap().buffer.len()
+    }
+
+    /// Total number of messages dropped because the buffer was full or they
+    /// were only partly sent
+    pub fn dropped_count(&self) -> u64 {
+        self.state.lock().unwrap().dropped
+    }
+
+    /// Send buffered messages, reconnecting immediately if needed
+    ///
+    /// Returns the error that stopped delivery, if any; the unsent messages
+    /// stay buffered.
+    pub fn try_flush(&self) -> io::Result<()> {
+        let mut state = self.state.lock().unwrap();
+        self.send_buffered(&mut state, true)?;
+        match state.connection.as_mut() {
+            Some(connection) => connection.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+impl SocketOutput {
+    fn send_buffered(&self, state: &mut SocketState, force_reconnect: bool) -> io::Result<()> {
+        if state.buffer.is_empty() {
+            return Ok(());
+        }
+
+        if state.connection.is_none() {
+            let due =
+                state.last_attempt.is_none_or(|at| at.elapsed() >= self.options.reconnect_delay);
+            if !force_reconnect && !due {
+                return Ok(());
+            }
+            state.last_attempt = Some(Instant::now());
+            state.connection = Some(self.connect()?);
+        }
+
+        while let Some(message) = state.buffer.front() {
+            let connection = state.connection.as_mut().expect("connection is open");
+            if let Err((written, e)) = connection.send(message, self.options.framing) {
+                state.connection = None;
+                if written > 0 {
+                    // Resending would leave a truncated copy before the full one
+                    state.buffer.pop_front();
+                    state.dropped += 1;
+                }
+                return Err(e);
+            }
+            state.buffer.pop_front();
+        }
+        Ok(())
+    }
+
+    fn connect(&self) -> io::Result<Connection> {
+        match &self.target {
+            SocketTarget::Udp(addr) => {
+                let remote = resolve(addr)?;
+                let local = if remote[0].is_ipv4() {
+                    "0.0.0.0:0"
+                } else {
+                    "[::]:0"
+                };
+                let socket = UdpSocket::bind(local)?;
+                socket.connect(&remote[..])?;
+                Ok(Connection::Udp(socket))
+            }
+            SocketTarget::Tcp(addr) => {
+                let mut last_error = None;
+                for remote in resolve(addr)? {
+                    match TcpStream::connect_timeout(&remote, self.options.connect_timeout) {
+                        Ok(stream) => {
+                            stream.set_write_timeout(Some(self.options.write_timeout))?;
+                            stream.set_nodelay(true)?;
+                            return Ok(Connection::Tcp(stream));
+                        }
+                        Err(e) => last_error = Some(e),
+                    }
+                }
+                Err(last_error.expect("resolve returns at least one address"))
+            }
+            #[cfg(unix)]
+            SocketTarget::Unix(path) => {
+                let stream = UnixStream::connect(path)?;
+                stream.set_write_timeout(Some(self.options.write_timeout))?;
+                Ok(Connection::Unix(stream))
+            }
+        }
+    }
+}
+
+impl LogOutput for SocketOutput {
+    fn write(&self, formatted_message: &str) {
+        let mut state = self.state.lock().unwrap();
+        if state.buffer.len() >= self.options.buffer_capacity.max(1) {
+            state.buffer.pop_front();
+            state.dropped += 1;
+        }
+        state.buffer.push_back(formatted_message.to_string());
+        // Failures leave the message buffered for the next attempt
+        let _ = self.send_buffered(&mut state, false);
+    }
+
+    fn flush(&self) {
+        let _ = self.try_flush();
+    }
+}
+
+fn write_frame(stream: &mut impl Write, frame: &[u8]) -> Result<(), (usize, io::Error)> {
+    let mut written = 0;
+    while written < frame.len() {
+        match stream.write(&frame[written..]) {
+            Ok(0) => return Err((written, io::ErrorKind::WriteZero.into())),
+            Ok(n) => written += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err((written, e)),
+        }
+    }
+    Ok(())
+}
+
+fn resolve(addr: &str) -> io::Result<Vec<std::net::SocketAddr>> {
+    let addrs: Vec<_> = addr.to_socket_addrs()?.collect();
+    if addrs.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::AddrNotAvailable,
+            format!("no address found for {addr}"),
+        ));
+    }
+    Ok(addrs)
+}
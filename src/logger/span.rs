@@ -0,0 +1,258 @@
+//! Timed spans with duration logging
+
+use super::{LogEntry, LogLevel, Logger, current_context, push_context, with_task_context};
+use serde_json::{Value, json};
+use std::fmt::Display;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+static NEXT_SPAN_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Span options
+#[derive(Debug, Clone)]
+pub struct SpanOptions {
+    /// Level of the enter and successful exit entries
+    pub level: LogLevel,
+    /// Log an entry when the span starts, not only when it ends
+    pub log_enter: bool,
+}
+
+impl Default for SpanOptions {
+    fn default() -> Self {
+        Self {
+            level: LogLevel::Info,
+            log_enter: true,
+        }
+    }
+}
+
+/// A running span; logs its exit with the elapsed time when finished or dropped
+///
+/// A span dropped while its thread panics logs `outcome = "panicked"` at
+/// `ERROR` level. The span id is exposed to the log context as `span_id` inside
+/// [`Span::in_scope`] and [`Span::instrument`], so entries logged there carry it
+/// and spans started there record it as their `parent_span_id`.
+#[derive(Debug)]
+pub struct Span {
+    logger: Logger,
+    name: String,
+    id: u64,
+    parent_id: Option<u64>,
+    options: SpanOptions,
+    start: Instant,
+    finished: bool,
+    /// Whether dropping the span unfinished means its work was cancelled
+    cancel_on_drop: bool,
+}
+
+/// How a span ended
+enum Outcome {
+    Ok,
+    Error(String),
+    Panicked,
+    Cancelled,
+}
+
+impl Span {
+    fn start(logger: &Logger, name: &str, options: SpanOptions) -> Self {
+        let span = Self {
+            logger: logger.clone(),
+            name: name.to_string(),
+            id: NEXT_SPAN_ID.fetch_add(1, Ordering::Relaxed),
+            parent_id: current_context().get("span_id").and_then(Value::as_u64),
+            options,
+            start: Instant::now(),
+            finished: false,
+            cancel_on_drop: false,
+        };
+        if span.options.log_enter {
+            span.emit(span.options.level, format!("{} started", span.name), None);
+        }
+        span
+    }
+
+    /// Get the span id
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Get the id of the enclosing span
+    pub fn parent_id(&self) -> Option<u64> {
+        self.parent_id
+    }
+
+    /// Get the span name
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Time elapsed since the span started
+    pub fn elapsed(&self) -> Duration {
+        self.start.elapsed()
+    }
+
+    /// Run a closure with this span as the current span of the thread
+    pub fn in_scope<F: FnOnce() -> R, R>(&self, f: F) -> R {
+        let _guard = push_context([("span_id", json!(self.id))]);
+        f()
+    }
+
+    /// Run a future with this span as the current span of its task
+    pub async fn instrument<F: Future>(&self, future: F) -> F::Output {
+        with_task_context([("span_id", json!(self.id))], future).await
+    }
+
+    /// Finish the span successfully
+    pub fn finish(mut self) {
+        self.finished = true;
+        let message = format!(
+            "{} finished in {:.3}ms",
+            self.name,
+            elapsed_ms(self.elapsed())
+        );
+        self.emit(self.options.level, message, Some(Outcome::Ok));
+    }
+
+    /// Finish the span with an error, logged at `ERROR` level
+    pub fn finish_with_error(mut self, error: &dyn Display) {
+        self.finished = true;
+        let message = format!(
+            "{} failed in {:.3}ms",
+            self.name,
+            elapsed_ms(self.elapsed())
+        );
+        self.emit(
+            LogLevel::Error,
+            message,
+            Some(Outcome::Error(error.to_string())),
+        );
+    }
+
+    fn emit(&self, level: LogLevel, message: String, outcome: Option<Outcome>) {
+        if !self.logger.is_enabled(level) {
+            return;
+        }
+
+        let mut entry = LogEntry::new(level, self.logger.name().to_string(), message)
+            .with_metadata("span".to_string(), json!(self.name))
+            .with_metadata("span_id".to_string(), json!(self.id));
+        if let Some(parent_id) = self.parent_id {
+            entry = entry.with_metadata("parent_span_id".to_string(), json!(parent_id));
+        }
+        if let Some(outcome) = outcome {
+            entry =
+                entry.with_metadata("elapsed_ms".to_string(), json!(elapsed_ms(self.elapsed())));
+            entry = match outcome {
+                Outcome::Ok => entry.with_metadata("outcome".to_string(), json!("ok")),
+                Outcome::Error(error) => entry
+                    .with_metadata("outcome".to_string(), json!("error"))
+                    .with_metadata("error".to_string(), json!(error)),
+                Outcome::Panicked => entry.with_metadata("outcome".to_string(), json!("panicked")),
+                Outcome::Cancelled => {
+                    entry.with_metadata("outcome".to_string(), json!("cancelled"))
+                }
+            };
+        }
+        self.logger.log_entry(entry);
+    }
+}
+
+impl Drop for Span {
+    fn drop(&mut self) {
+        if self.finished {
+            return;
+        }
+        self.finished = true;
+        let elapsed = elapsed_ms(self.elapsed());
+        if std::thread::panicking() {
+            let message = format!("{} panicked after {elapsed:.3}ms", self.name);
+            self.emit(LogLevel::Error, message, Some(Outcome::Panicked));
+        } else if self.cancel_on_drop {
+            let message = format!("{} cancelled after {elapsed:.3}ms", self.name);
+            self.emit(LogLevel::Warn, message, Some(Outcome::Cancelled));
+        } else {
+            let message = format!("{} finished in {elapsed:.3}ms", self.name);
+            self.emit(self.options.level, message, Some(Outcome::Ok));
+        }
+    }
+}
+
+fn elapsed_ms(elapsed: Duration) -> f64 {
+    elapsed.as_secs_f64() * 1000.0
+}
+
+impl Logger {
+    /// Start a span with default options
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use mudssky_utils::logger::Logger;
+    ///
+    /// let logger = Logger::with_name("app");
+    /// let span = logger.span("load_config");
+    /// // ... work ...
+    /// span.finish();
+    /// ```
+    pub fn span(&self, name: &str) -> Span {
+        Span::start(self, name, SpanOptions::default())
+    }
+
+    /// Start a span with custom options
+    pub fn span_with(&self, name: &str, options: SpanOptions) -> Span {
+        Span::start(self, name, options)
+    }
+
+    /// Time a closure, logging `ERROR` with the error when it returns `Err`
+    ///
+    /// A panic in the closure is logged with `outcome = "panicked"`.
+    pub fn time<T, E, F>(&self, name: &str, f: F) -> Result<T, E>
+    where
+        E: Display,
+        F: FnOnce() -> Result<T, E>,
+    {
+        let span = self.span(name);
+        let result = span.in_scope(f);
+        match &result {
+            Ok(_) => span.finish(),
+            Err(error) => span.finish_with_error(error),
+        }
+        result
+    }
+
+    /// Time a future across `.await` points, logging `ERROR` when it yields `Err`
+    ///
+    /// When the returned future is dropped before completing, the span logs
+    /// `outcome = "cancelled"` at `WARN` level.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use mudssky_utils::logger::Logger;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let logger = Logger::with_name("app");
+    /// let rows = logger
+    ///     .time_async("query", async { Ok::<_, String>(3) })
+    ///     .await
+    ///     .unwrap();
+    /// assert_eq!(rows, 3);
+    /// # }
+    /// ```
+    pub async fn time_async<T, E, F>(&self, name: &str, future: F) -> Result<T, E>
+    where
+        E: Display,
+        F: Future<Output = Result<T, E>>,
+    {
+        let mut span = self.span(name);
+        span.cancel_on_drop = true;
+        let result = span.instrument(future).await;
+        match &result {
+            Ok(_) => span.finish(),
+            Err(error) => span.finish_with_error(error),
+        }
+        result
+    }
+}
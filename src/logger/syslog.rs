@@ -0,0 +1,206 @@
+//! RFC 5424 syslog formatter
+
+use super::{LogEntry, LogFormatter, LogLevel};
+use chrono::SecondsFormat;
+use serde_json::Value;
+
+/// Syslog facility codes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyslogFacility {
+    Kern = 0,
+    User = 1,
+    Mail = 2,
+    Daemon = 3,
+    Auth = 4,
+    Syslog = 5,
+    Lpr = 6,
+    News = 7,
+    Uucp = 8,
+    Cron = 9,
+    AuthPriv = 10,
+    Ftp = 11,
+    Local0 = 16,
+    Local1 = 17,
+    Local2 = 18,
+    Local3 = 19,
+    Local4 = 20,
+    Local5 = 21,
+    Local6 = 22,
+    Local7 = 23,
+}
+
+/// Map a log level to a syslog severity
+///
+/// `TRACE` and `DEBUG` both map to debug (7).
+pub fn syslog_severity(level: LogLevel) -> u8 {
+    match level {
+        LogLevel::Trace | LogLevel::Debug => 7,
+        LogLevel::Info => 6,
+        LogLevel::Warn => 4,
+        LogLevel::Error => 3,
+    }
+}
+
+/// Formatter producing RFC 5424 syslog lines
+///
+/// Lines have the form
+/// `<PRI>1 TIMESTAMP HOSTNAME APP-NAME PROCID MSGID [SD-ID key="value"...] MSG`,
+/// with the logger name as `MSGID` and the metadata as a single
+/// structured-data element.
+#[derive(Debug, Clone)]
+pub struct SyslogFormatter {
+    facility: SyslogFacility,
+    hostname: String,
+    app_name: String,
+    sd_id: String,
+}
+
+impl Default for SyslogFormatter {
+    fn default() -> Self {
+        let app_name = std::env::current_exe()
+            .ok()
+            .and_then(|path| path.file_stem().map(|s| s.to_string_lossy().to_string()))
+            .unwrap_or_default();
+        Self::new(app_name)
+    }
+}
+
+impl SyslogFormatter {
+    /// Create a syslog formatter for an application
+    ///
+    /// Uses the `user` facility, the detected hostname and `meta@32473` as the
+    /// structured-data id.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use mudssky_utils::logger::{LogEntry, LogFormatter, LogLevel, SyslogFacility, SyslogFormatter};
+    ///
+    /// let formatter = SyslogFormatter::new("billing")
+    ///     .with_facility(SyslogFacility::Local0)
+    ///     .with_hostname("web-1");
+    /// let entry = LogEntry::new(LogLevel::Error, "api".to_string(), "failed".to_string());
+    /// assert!(formatter.format(&entry).starts_with("<131>1 "));
+    /// ```
+    pub fn new(app_name: impl Into<String>) -> Self {
+        Self {
+            facility: SyslogFacility::User,
+            hostname: detect_hostname(),
+            app_name: app_name.into(),
+            sd_id: "meta@32473".to_string(),
+        }
+    }
+
+    /// Set the facility
+    pub fn with_facility(mut self, facility: SyslogFacility) -> Self {
+        self.facility = facility;
+        self
+    }
+
+    /// Set the hostname
+    pub fn with_hostname(mut self, hostname: impl Into<String>) -> Self {
+        self.hostname = hostname.into();
+        self
+    }
+
+    /// Set the structured-data id used for metadata (`name@enterprise-number`)
+    pub fn with_sd_id(mut self, sd_id: impl Into<String>) -> Self {
+        self.sd_id = sd_id.into();
+        self
+    }
+
+    fn structured_data(&self, entry: &LogEntry) -> String {
+        if entry.metadata.is_empty() {
+            return "-".to_string();
+        }
+
+        let mut keys: Vec<&String> = entry.metadata.keys().collect();
+        keys.sort();
+
+        let mut sd = format!("[{}", sd_name(&self.sd_id));
+        for key in keys {
+            let value = match &entry.metadata[key] {
+                Value::String(s) => s.clone(),
+                other => other.to_string(),
+            };
+            sd.push_str(&format!(
+                " {}=\"{}\"",
+                sd_name(key),
+                escape_param_value(&value)
+            ));
+        }
+        sd.push(']');
+        sd
+    }
+}
+
+impl LogFormatter for SyslogFormatter {
+    fn format(&self, entry: &LogEntry) -> String {
+        let pri = (self.facility as u8) * 8 + syslog_severity(entry.level);
+        format!(
+            "<{pri}>1 {} {} {} {} {} {} {}",
+            entry.timestamp.to_rfc3339_opts(SecondsFormat::Micros, true),
+            header_field(&self.hostname, 255),
+            header_field(&self.app_name, 48),
+            std::process::id(),
+            header_field(&entry.logger_name, 32),
+            self.structured_data(entry),
+            entry.message
+        )
+    }
+}
+
+/// Restrict a header field to printable ASCII, using `-` for empty values
+fn header_field(value: &str, max_len: usize) -> String {
+    let field: String = value.chars().filter(|c| c.is_ascii_graphic()).take(max_len).collect();
+    if field.is_empty() {
+        "-".to_string()
+    } else {
+        field
+    }
+}
+
+/// Restrict an SD-ID or PARAM-NAME to the characters RFC 5424 allows
+fn sd_name(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_graphic() && !matches!(c, '=' | ']' | '"') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .take(32)
+        .collect();
+    if name.is_empty() {
+        "_".to_string()
+    } else {
+        name
+    }
+}
+
+fn escape_param_value(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, '"' | '\\' | ']') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+fn detect_hostname() -> String {
+    ["HOSTNAME", "COMPUTERNAME"]
+        .iter()
+        .find_map(|var| std::env::var(var).ok().filter(|v| !v.is_empty()))
+        .or_else(|| {
+            std::fs::read_to_string("/proc/sys/kernel/hostname")
+                .or_else(|_| std::fs::read_to_string("/etc/hostname"))
+                .ok()
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+        })
+        .unwrap_or_else(|| "-".to_string())
+}
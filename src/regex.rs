@@ -196,3 +196,24 @@ pub fn matches_pattern(text: &str, pattern: &str) -> Result<bool, regex::Error>
     let re = Regex::new(pattern)?;
     Ok(re.is_match(text))
 }
+
+/// Turn an anchored validation pattern into one that finds matches inside text
+///
+/// Strips the leading `^` and trailing `$` and wraps the rest in word
+/// boundaries, so e.g. the `email` pattern can scan a whole sentence.
+///
+/// # Examples
+///
+/// ```
+/// use mudssky_utils::regex::{get_patterns, to_search_pattern};
+///
+/// let re = to_search_pattern(&get_patterns().mobile_cn).unwrap();
+/// assert!(re.is_match("call 13812345678 now"));
+/// assert!(!re.is_match("id 1381234567890"));
+/// ```
+pub fn to_search_pattern(pattern: &Regex) -> Result<Regex, regex::Error> {
+    let source = pattern.as_str();
+    let source = source.strip_prefix('^').unwrap_or(source);
+    let source = source.strip_suffix('$').unwrap_or(source);
+    Regex::new(&format!(r"\b(?:{source})\b"))
+}
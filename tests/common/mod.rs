@@ -0,0 +1,39 @@
+//! Helpers shared by the integration tests
+#![allow(dead_code)]
+
+use mudssky_utils::logger::LogOutput;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+/// Output that keeps every formatted line in memory
+#[derive(Debug, Clone)]
+pub struct TestOutput {
+    messages: Arc<Mutex<Vec<String>>>,
+}
+
+impl TestOutput {
+    pub fn new() -> Self {
+        Self {
+            messages: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    pub fn get_messages(&self) -> Vec<String> {
+        self.messages.lock().unwrap().clone()
+    }
+}
+
+impl LogOutput for TestOutput {
+    fn write(&self, formatted_message: &str) {
+        self.messages.lock().unwrap().push(formatted_message.to_string());
+    }
+}
+
+/// Create an empty directory under the system temp dir, unique to this process
+pub fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("mudssky_utils_{name}_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
@@ -156,3 +156,14 @@ fn test_conditional_execution() {
         assert!(release_executed);
     }
 }
+
+#[test]
+fn test_color_detection() {
+    assert!(color_decision(None, Some("xterm-256color"), true));
+    assert!(color_decision(Some(""), None, true));
+    // NO_COLOR wins over a terminal, whatever its value
+    assert!(!color_decision(Some("1"), Some("xterm-256color"), true));
+    assert!(!color_decision(Some("0"), None, true));
+    assert!(!color_decision(None, Some("dumb"), true));
+    assert!(!color_decision(None, Some("xterm"), false));
+}
@@ -94,6 +94,26 @@ fn test_error_chain() {
     }
 }
 
+#[test]
+fn test_utils_error_kind() {
+    assert_eq!(UtilsError::from(ArgumentError::new("a")).kind(), "Argument");
+    assert_eq!(
+        UtilsError::from(ValidationError::new("f", "m")).kind(),
+        "Validation"
+    );
+    assert_eq!(
+        UtilsError::from(ConfigError::new("k", "m")).kind(),
+        "Config"
+    );
+    assert_eq!(
+        UtilsError::from(NetworkError::new("op", "m")).kind(),
+        "Network"
+    );
+    assert_eq!(UtilsError::from(ParseError::new("i", "e")).kind(), "Parse");
+    let io_err = std::io::Error::other("disk");
+    assert_eq!(UtilsError::from(io_err).kind(), "Io");
+}
+
 #[test]
 fn test_error_cloning() {
     let arg_err = ArgumentError::new("test");
@@ -64,6 +64,153 @@ async fn test_debouncer_cancel() {
     assert!(result.is_err());
 }
 
+/// Shared log of the arguments a callback was invoked with, and a closure appending to it
+fn call_log() -> (
+    Arc<std::sync::Mutex<Vec<usize>>>,
+    impl Fn(usize) + Send + Sync + 'static,
+) {
+    let calls = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let log = calls.clone();
+    (calls, move |n| log.lock().unwrap().push(n))
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_debounced_collapses_burst() {
+    let (calls, record) = call_log();
+    let debounced = Debounced::new(
+        Duration::from_millis(40),
+        DebounceOptions::default(),
+        move |n: usize| {
+            record(n);
+            async {}
+        },
+    );
+
+    let mut handles = Vec::new();
+    for n in 1..=10 {
+        let debounced = debounced.clone();
+        handles.push(tokio::spawn(async move {
+            sleep(Duration::from_millis(n as u64)).await;
+            debounced.call(n);
+        }));
+    }
+    for handle in handles {
+        handle.await.unwrap();
+    }
+    assert!(debounced.is_pending());
+    assert!(calls.lock().unwrap().is_empty());
+
+    sleep(Duration::from_millis(100)).await;
+    assert_eq!(*calls.lock().unwrap(), vec![10]);
+    assert!(!debounced.is_pending());
+    assert_eq!(debounced.invocation_count(), 1);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_debounced_leading_and_trailing() {
+    let options = DebounceOptions {
+        leading: true,
+        trailing: true,
+    };
+    let (calls, record) = call_log();
+    let debounced = Debounced::new(Duration::from_millis(40), options, move |n: usize| {
+        record(n);
+        async {}
+    });
+
+    debounced.call(1);
+    sleep(Duration::from_millis(5)).await;
+    assert_eq!(*calls.lock().unwrap(), vec![1]);
+    debounced.call(2);
+    debounced.call(3);
+    sleep(Duration::from_millis(100)).await;
+    assert_eq!(*calls.lock().unwrap(), vec![1, 3]);
+
+    // A single call only fires on the leading edge
+    debounced.call(4);
+    sleep(Duration::from_millis(100)).await;
+    assert_eq!(*calls.lock().unwrap(), vec![1, 3, 4]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_debounced_leading_only() {
+    let options = DebounceOptions {
+        leading: true,
+        trailing: false,
+    };
+    let (calls, record) = call_log();
+    let debounced = Debounced::new(Duration::from_millis(40), options, move |n: usize| {
+        record(n);
+        async {}
+    });
+
+    for n in 1..=5 {
+        debounced.call(n);
+    }
+    sleep(Duration::from_millis(100)).await;
+    assert_eq!(*calls.lock().unwrap(), vec![1]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_debounced_max_wait() {
+    let (calls, record) = call_log();
+    let debounced = Debounced::new(
+        Duration::from_millis(50),
+        DebounceOptions::default(),
+        move |n: usize| {
+            record(n);
+            async {}
+        },
+    )
+    .with_max_wait(Duration::from_millis(80));
+
+    let start = tokio::time::Instant::now();
+    let mut n = 0;
+    while start.elapsed() < Duration::from_millis(300) {
+        n += 1;
+        debounced.call(n);
+        sleep(Duration::from_millis(10)).await;
+    }
+    sleep(Duration::from_millis(120)).await;
+
+    let calls = calls.lock().unwrap();
+    // Without max_wait the continuous burst would only fire once at the end
+    assert_eq!(n, 30);
+    assert_eq!(*calls, vec![9, 17, 25, 30]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_debounced_flush_and_cancel() {
+    let (calls, record) = call_log();
+    let debounced = Debounced::new(
+        Duration::from_millis(200),
+        DebounceOptions::default(),
+        move |n: usize| {
+            record(n);
+            async {}
+        },
+    );
+
+    assert!(!debounced.flush().await);
+    debounced.call(1);
+    debounced.call(2);
+    assert!(debounced.flush().await);
+    assert_eq!(*calls.lock().unwrap(), vec![2]);
+    assert!(!debounced.is_pending());
+
+    debounced.call(3);
+    debounced.cancel();
+    assert!(!debounced.is_pending());
+    assert!(!debounced.flush().await);
+
+    // Usable again after cancel
+    debounced.call(4);
+    assert!(debounced.flush().await);
+    sleep(Duration::from_millis(250)).await;
+    assert_eq!(*calls.lock().unwrap(), vec![2, 4]);
+    assert_eq!(debounced.invocation_count(), 2);
+}
+
 #[tokio::test]
 async fn test_throttler_leading() {
     let throttler = Throttler::new(
@@ -136,6 +283,128 @@ async fn test_throttler_cancel() {
     assert!(result.is_err());
 }
 
+#[tokio::test(start_paused = true)]
+async fn test_throttled_default_options_run_trailing_call() {
+    let (calls, record) = call_log();
+    let throttled = Throttled::new(
+        Duration::from_millis(30),
+        ThrottleOptions::default(),
+        move |n: usize| {
+            record(n);
+            async move { n * 10 }
+        },
+    );
+
+    let (a, b, c) = tokio::join!(throttled.call(1), throttled.call(2), throttled.call(3));
+    assert_eq!((a, b, c), (Some(30), Some(30), Some(30)));
+    assert_eq!(*calls.lock().unwrap(), vec![3]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_throttled_leading_and_trailing() {
+    let options = ThrottleOptions {
+        leading: true,
+        trailing: true,
+    };
+    let (calls, record) = call_log();
+    let throttled = Throttled::new(Duration::from_millis(40), options, move |n: usize| {
+        record(n);
+        async move { n * 10 }
+    });
+
+    assert_eq!(throttled.call(1).await, Some(10));
+    let start = tokio::time::Instant::now();
+    let (a, b) = tokio::join!(throttled.call(2), throttled.call(3));
+    assert_eq!((a, b), (Some(30), Some(30)));
+    assert_eq!(start.elapsed(), Duration::from_millis(40));
+    assert_eq!(*calls.lock().unwrap(), vec![1, 3]);
+    assert_eq!(throttled.invocation_count(), 2);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_throttled_at_most_once_per_window() {
+    let options = ThrottleOptions {
+        leading: true,
+        trailing: true,
+    };
+    let (calls, record) = call_log();
+    let throttled = Throttled::new(Duration::from_millis(50), options, move |n: usize| {
+        record(n);
+        async move { n * 10 }
+    });
+
+    // A call every 5ms for 220ms
+    let mut handles = Vec::new();
+    for n in 1..=44 {
+        let throttled = throttled.clone();
+        handles.push(tokio::spawn(async move { throttled.call(n).await }));
+        tokio::time::advance(Duration::from_millis(5)).await;
+    }
+    for handle in handles {
+        assert!(handle.await.unwrap().is_some());
+    }
+
+    // One leading call plus one trailing call per 50ms window afterwards
+    assert_eq!(*calls.lock().unwrap(), vec![1, 11, 22, 33, 44]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_throttled_without_trailing_returns_last_result() {
+    let options = ThrottleOptions {
+        leading: true,
+        trailing: false,
+    };
+    let (calls, record) = call_log();
+    let throttled = Throttled::new(Duration::from_millis(40), options, move |n: usize| {
+        record(n);
+        async move { n * 10 }
+    });
+
+    assert_eq!(throttled.call(1).await, Some(10));
+    assert_eq!(throttled.call(2).await, Some(10));
+    tokio::time::advance(Duration::from_millis(60)).await;
+    assert_eq!(throttled.call(3).await, Some(30));
+    assert_eq!(*calls.lock().unwrap(), vec![1, 3]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_throttled_flush_and_cancel() {
+    let options = ThrottleOptions {
+        leading: true,
+        trailing: true,
+    };
+    let (calls, record) = call_log();
+    let throttled = Throttled::new(Duration::from_millis(200), options, move |n: usize| {
+        record(n);
+        async move { n * 10 }
+    });
+
+    assert_eq!(throttled.flush().await, None);
+    assert_eq!(throttled.call(1).await, Some(10));
+
+    let waiter = {
+        let throttled = throttled.clone();
+        tokio::spawn(async move { throttled.call(2).await })
+    };
+    tokio::time::advance(Duration::from_millis(10)).await;
+    assert!(throttled.is_pending());
+    assert_eq!(throttled.flush().await, Some(20));
+    assert_eq!(waiter.await.unwrap(), Some(20));
+
+    let waiter = {
+        let throttled = throttled.clone();
+        tokio::spawn(async move { throttled.call(3).await })
+    };
+    tokio::time::advance(Duration::from_millis(10)).await;
+    throttled.cancel();
+    assert_eq!(waiter.await.unwrap(), None);
+    assert!(!throttled.is_pending());
+
+    // The next call opens a fresh window
+    assert_eq!(throttled.call(4).await, Some(40));
+    assert_eq!(*calls.lock().unwrap(), vec![1, 2, 4]);
+}
+
 #[tokio::test]
 async fn test_poller_success() {
     let poller = Poller::new(PollingOptions {
@@ -317,6 +586,9 @@ fn test_function_error_display() {
 
     let error = FunctionError::General("Test general".to_string());
     assert_eq!(error.to_string(), "Function error: Test general");
+
+    let error = FunctionError::CircuitOpen("Test circuit".to_string());
+    assert_eq!(error.to_string(), "Circuit open: Test circuit");
 }
 
 #[test]
@@ -349,3 +621,759 @@ fn test_retry_options_default() {
     assert_eq!(options.max_retries, 3);
     assert_eq!(options.delay, Duration::from_millis(0));
 }
+
+#[test]
+fn test_backoff_delays() {
+    let ms = Duration::from_millis;
+
+    assert_eq!(Backoff::Constant(ms(5)).delay(7, ms(0)), ms(5));
+
+    let linear = Backoff::Linear {
+        initial: ms(10),
+        increment: ms(5),
+        max: ms(22),
+    };
+    assert_eq!(linear.delay(1, ms(0)), ms(10));
+    assert_eq!(linear.delay(2, ms(0)), ms(15));
+    assert_eq!(linear.delay(4, ms(0)), ms(22));
+
+    let exponential = Backoff::Exponential {
+        initial: ms(10),
+        multiplier: 3.0,
+        max: ms(200),
+    };
+    assert_eq!(exponential.delay(1, ms(0)), ms(10));
+    assert_eq!(exponential.delay(2, ms(0)), ms(30));
+    assert_eq!(exponential.delay(4, ms(0)), ms(200));
+    assert_eq!(exponential.delay(usize::MAX, ms(0)), ms(200));
+
+    let full = Backoff::FullJitter {
+        base: ms(10),
+        max: ms(50),
+    };
+    for attempt in 1..20 {
+        let ceiling = ms(10 * 2u64.pow(attempt as u32 - 1)).min(ms(50));
+        assert!(full.delay(attempt, ms(0)) <= ceiling);
+    }
+
+    let decorrelated = Backoff::DecorrelatedJitter {
+        base: ms(10),
+        max: ms(100),
+    };
+    let mut previous = ms(0);
+    for attempt in 1..20 {
+        let delay = decorrelated.delay(attempt, previous);
+        assert!(delay >= ms(10) && delay <= ms(100));
+        assert!(delay <= previous.max(ms(10)) * 3);
+        previous = delay;
+    }
+}
+
+#[tokio::test]
+async fn test_with_retry_policy_on_retry_hook() {
+    let counter = Arc::new(AtomicUsize::new(0));
+    let counter_clone = counter.clone();
+    let retries = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let retries_clone = retries.clone();
+
+    let policy = RetryPolicy::new(5)
+        .with_backoff(Backoff::exponential(
+            Duration::from_millis(1),
+            Duration::from_millis(3),
+        ))
+        .on_retry(move |attempt, error, delay| {
+            retries_clone.lock().unwrap().push((attempt, error.to_string(), delay));
+        });
+
+    let result = with_retry_policy(
+        || async {
+            let n = counter_clone.fetch_add(1, Ordering::Relaxed);
+            if n < 3 {
+                Err::<usize, Box<dyn std::error::Error + Send + Sync>>(format!("fail {n}").into())
+            } else {
+                Ok(n)
+            }
+        },
+        &policy,
+    )
+    .await;
+
+    assert_eq!(result.unwrap(), 3);
+    let retries = retries.lock().unwrap();
+    assert_eq!(
+        *retries,
+        vec![
+            (1, "fail 0".to_string(), Duration::from_millis(1)),
+            (2, "fail 1".to_string(), Duration::from_millis(2)),
+            (3, "fail 2".to_string(), Duration::from_millis(3)),
+        ]
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_with_retry_policy_elapsed_budget() {
+    let counter = Arc::new(AtomicUsize::new(0));
+    let counter_clone = counter.clone();
+
+    let policy = RetryPolicy::new(100)
+        .with_backoff(Backoff::Constant(Duration::from_millis(20)))
+        .with_max_elapsed(Duration::from_millis(70));
+
+    let start = tokio::time::Instant::now();
+    let result = with_retry_policy(
+        || async {
+            counter_clone.fetch_add(1, Ordering::Relaxed);
+            Err::<(), Box<dyn std::error::Error + Send + Sync>>("down".into())
+        },
+        &policy,
+    )
+    .await;
+
+    // The paused clock only moves by the backoff sleeps: retries at 20, 40
+    // and 60ms, while a fourth at 80ms would exceed the budget
+    assert_eq!(start.elapsed(), Duration::from_millis(60));
+    assert_eq!(counter.load(Ordering::Relaxed), 4);
+    match result {
+        Err(FunctionError::RetryExhausted(msg)) => assert!(msg.contains("Retry budget")),
+        other => panic!("Expected RetryExhausted, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_retry_policy_from_options() {
+    let policy = RetryPolicy::from(RetryOptions {
+        max_retries: 4,
+        delay: Duration::from_millis(7),
+    });
+    assert_eq!(policy.max_retries, 4);
+    assert_eq!(policy.backoff, Backoff::Constant(Duration::from_millis(7)));
+    assert_eq!(policy.max_elapsed, None);
+}
+
+#[tokio::test]
+async fn test_with_retry_typed_keeps_all_errors() {
+    use mudssky_utils::error::NetworkError;
+
+    let counter = Arc::new(AtomicUsize::new(0));
+    let counter_clone = counter.clone();
+
+    let result = with_retry_typed(
+        || async {
+            let n = counter_clone.fetch_add(1, Ordering::Relaxed);
+            Err::<(), _>(NetworkError::with_status(
+                "fetch",
+                format!("attempt {n}"),
+                503,
+            ))
+        },
+        &RetryPolicy::new(2),
+    )
+    .await;
+
+    let error = result.unwrap_err();
+    assert_eq!(error.stop(), RetryStop::Exhausted);
+    assert_eq!(error.attempts(), 3);
+    assert_eq!(error.last().message(), "attempt 2");
+    let messages: Vec<&str> = error.errors().iter().map(|e| e.message()).collect();
+    assert_eq!(messages, vec!["attempt 0", "attempt 1", "attempt 2"]);
+    assert!(error.to_string().starts_with("retries exhausted after 3 attempts"));
+    assert!(std::error::Error::source(&error).is_some());
+    assert_eq!(error.into_last().status_code(), Some(503));
+}
+
+#[tokio::test]
+async fn test_with_retry_typed_success_after_failures() {
+    let counter = Arc::new(AtomicUsize::new(0));
+    let counter_clone = counter.clone();
+
+    let result = with_retry_typed(
+        || async {
+            match counter_clone.fetch_add(1, Ordering::Relaxed) {
+                0 => Err(std::io::Error::other("busy")),
+                n => Ok(n),
+            }
+        },
+        &RetryPolicy::new(3),
+    )
+    .await;
+
+    assert_eq!(result.unwrap(), 1);
+}
+
+#[tokio::test]
+async fn test_retry_predicate_stops_on_fatal_errors() {
+    use mudssky_utils::error::{NetworkError, UtilsError};
+
+    let counter = Arc::new(AtomicUsize::new(0));
+    let counter_clone = counter.clone();
+    let policy = RetryPolicy::new(5).retry_if(is_retryable_error);
+
+    // 429 then 500 are retried, the 403 stops immediately
+    let result = with_retry_typed(
+        || async {
+            let status = [429, 500, 403, 200][counter_clone.fetch_add(1, Ordering::Relaxed)];
+            Err::<(), _>(UtilsError::from(NetworkError::with_status(
+                "call", "failed", status,
+            )))
+        },
+        &policy,
+    )
+    .await;
+
+    let error = result.unwrap_err();
+    assert_eq!(error.stop(), RetryStop::NotRetryable);
+    assert_eq!(error.attempts(), 3);
+    assert_eq!(counter.load(Ordering::Relaxed), 3);
+
+    let boxed = with_retry_policy(
+        || async {
+            Err::<(), Box<dyn std::error::Error + Send + Sync>>(Box::new(
+                NetworkError::with_status("call", "bad request", 400),
+            ))
+        },
+        &policy,
+    )
+    .await;
+    match boxed {
+        Err(FunctionError::RetryExhausted(msg)) => {
+            assert!(msg.starts_with("Non-retryable error after 0 retries"))
+        }
+        other => panic!("Expected RetryExhausted, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_is_retryable_error() {
+    use mudssky_utils::error::{NetworkError, UtilsError};
+
+    assert!(is_retryable_error(&NetworkError::new("connect", "refused")));
+    assert!(is_retryable_error(&NetworkError::with_status(
+        "get",
+        "bad gateway",
+        502
+    )));
+    assert!(is_retryable_error(&NetworkError::with_status(
+        "get", "too many", 429
+    )));
+    assert!(!is_retryable_error(&NetworkError::with_status(
+        "get",
+        "unauthorized",
+        401
+    )));
+    assert!(!is_retryable_error(&UtilsError::from(
+        NetworkError::with_status("get", "gone", 410)
+    )));
+    assert!(is_retryable_error(&std::io::Error::other("disk")));
+}
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+async fn fail(breaker: &CircuitBreaker) -> Result<(), BoxError> {
+    breaker.call(|| async { Err::<(), BoxError>("down".into()) }).await
+}
+
+async fn succeed(breaker: &CircuitBreaker) -> Result<(), BoxError> {
+    breaker.call(|| async { Ok::<(), BoxError>(()) }).await
+}
+
+#[tokio::test]
+async fn test_circuit_breaker_opens_and_fails_fast() {
+    let transitions = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let transitions_clone = transitions.clone();
+    let breaker = CircuitBreaker::new(CircuitBreakerOptions {
+        failure_threshold: 3,
+        window: FailureWindow::Count(5),
+        open_duration: Duration::from_secs(60),
+        ..Default::default()
+    })
+    .on_state_change(move |from, to| transitions_clone.lock().unwrap().push((from, to)));
+
+    fail(&breaker).await.unwrap_err();
+    succeed(&breaker).await.unwrap();
+    fail(&breaker).await.unwrap_err();
+    assert_eq!(breaker.state(), CircuitState::Closed);
+    assert_eq!(breaker.status().failure_count, 2);
+    fail(&breaker).await.unwrap_err();
+    assert_eq!(breaker.state(), CircuitState::Open);
+
+    let ran = Arc::new(AtomicUsize::new(0));
+    let ran_clone = ran.clone();
+    let result = breaker
+        .call(|| async move {
+            ran_clone.fetch_add(1, Ordering::Relaxed);
+            Ok::<(), BoxError>(())
+        })
+        .await;
+    let error = result.unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<FunctionError>(),
+        Some(FunctionError::CircuitOpen(_))
+    ));
+    assert_eq!(ran.load(Ordering::Relaxed), 0);
+
+    let status = breaker.status();
+    assert_eq!(status.state, CircuitState::Open);
+    assert_eq!(status.total_calls, 4);
+    assert_eq!(status.rejected_calls, 1);
+    assert!(status.open_remaining.unwrap() > Duration::from_secs(50));
+    assert_eq!(
+        *transitions.lock().unwrap(),
+        vec![(CircuitState::Closed, CircuitState::Open)]
+    );
+}
+
+#[tokio::test]
+async fn test_circuit_breaker_count_window_rolls() {
+    let breaker = CircuitBreaker::new(CircuitBreakerOptions {
+        failure_threshold: 2,
+        window: FailureWindow::Count(2),
+        ..Default::default()
+    });
+
+    // Failures separated by a success never share the two-call window
+    for _ in 0..3 {
+        fail(&breaker).await.unwrap_err();
+        succeed(&breaker).await.unwrap();
+    }
+    assert_eq!(breaker.state(), CircuitState::Closed);
+    fail(&breaker).await.unwrap_err();
+    fail(&breaker).await.unwrap_err();
+    assert_eq!(breaker.state(), CircuitState::Open);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_circuit_breaker_time_window() {
+    let breaker = CircuitBreaker::new(CircuitBreakerOptions {
+        failure_threshold: 2,
+        window: FailureWindow::Time(Duration::from_millis(40)),
+        ..Default::default()
+    });
+
+    fail(&breaker).await.unwrap_err();
+    sleep(Duration::from_millis(60)).await;
+    assert_eq!(breaker.status().failure_count, 0);
+    fail(&breaker).await.unwrap_err();
+    assert_eq!(breaker.state(), CircuitState::Closed);
+    fail(&breaker).await.unwrap_err();
+    assert_eq!(breaker.state(), CircuitState::Open);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_circuit_breaker_half_open_trials() {
+    let transitions = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let transitions_clone = transitions.clone();
+    let breaker = Arc::new(
+        CircuitBreaker::new(CircuitBreakerOptions {
+            failure_threshold: 1,
+            open_duration: Duration::from_millis(30),
+            half_open_max_calls: 1,
+            success_threshold: 2,
+            ..Default::default()
+        })
+        .on_state_change(move |from, to| transitions_clone.lock().unwrap().push((from, to))),
+    );
+
+    fail(&breaker).await.unwrap_err();
+    sleep(Duration::from_millis(40)).await;
+    assert_eq!(breaker.state(), CircuitState::HalfOpen);
+
+    // A failed trial re-opens the circuit
+    fail(&breaker).await.unwrap_err();
+    assert_eq!(breaker.state(), CircuitState::Open);
+    sleep(Duration::from_millis(40)).await;
+
+    // Only one trial call may run at a time
+    let slow = {
+        let breaker = breaker.clone();
+        tokio::spawn(async move {
+            breaker
+                .call(|| async {
+                    sleep(Duration::from_millis(30)).await;
+                    Ok::<(), BoxError>(())
+                })
+                .await
+        })
+    };
+    sleep(Duration::from_millis(10)).await;
+    assert!(succeed(&breaker).await.is_err());
+    slow.await.unwrap().unwrap();
+    assert_eq!(breaker.state(), CircuitState::HalfOpen);
+
+    succeed(&breaker).await.unwrap();
+    assert_eq!(breaker.state(), CircuitState::Closed);
+    assert_eq!(
+        *transitions.lock().unwrap(),
+        vec![
+            (CircuitState::Closed, CircuitState::Open),
+            (CircuitState::Open, CircuitState::HalfOpen),
+            (CircuitState::HalfOpen, CircuitState::Open),
+            (CircuitState::Open, CircuitState::HalfOpen),
+            (CircuitState::HalfOpen, CircuitState::Closed),
+        ]
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_circuit_breaker_reset_and_abandoned_trial() {
+    let breaker = CircuitBreaker::new(CircuitBreakerOptions {
+        failure_threshold: 1,
+        open_duration: Duration::from_millis(20),
+        ..Default::default()
+    });
+
+    fail(&breaker).await.unwrap_err();
+    sleep(Duration::from_millis(30)).await;
+
+    // A trial call that is dropped mid-flight frees its slot
+    let pending = breaker.call(|| async {
+        sleep(Duration::from_secs(10)).await;
+        Ok::<(), BoxError>(())
+    });
+    let _ = tokio::time::timeout(Duration::from_millis(10), pending).await;
+    succeed(&breaker).await.unwrap();
+    assert_eq!(breaker.state(), CircuitState::Closed);
+
+    fail(&breaker).await.unwrap_err();
+    assert_eq!(breaker.state(), CircuitState::Open);
+    breaker.reset();
+    assert_eq!(breaker.state(), CircuitState::Closed);
+    assert_eq!(breaker.status().failure_count, 0);
+    succeed(&breaker).await.unwrap();
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_circuit_breaker_ignores_trials_from_earlier_phase() {
+    let breaker = Arc::new(CircuitBreaker::new(CircuitBreakerOptions {
+        failure_threshold: 1,
+        open_duration: Duration::from_millis(40),
+        half_open_max_calls: 2,
+        ..Default::default()
+    }));
+    let trial = |duration: Duration| {
+        let breaker = breaker.clone();
+        tokio::spawn(async move {
+            breaker
+                .call(|| async move {
+                    sleep(duration).await;
+                    Ok::<(), BoxError>(())
+                })
+                .await
+        })
+    };
+
+    fail(&breaker).await.unwrap_err();
+    tokio::time::advance(Duration::from_millis(50)).await;
+
+    // Runs from the first half-open phase into the second one
+    let stale_trial = trial(Duration::from_millis(150));
+    tokio::task::yield_now().await;
+    fail(&breaker).await.unwrap_err();
+    assert_eq!(breaker.state(), CircuitState::Open);
+
+    tokio::time::advance(Duration::from_millis(50)).await;
+    let current_trial = trial(Duration::from_millis(250));
+    tokio::task::yield_now().await;
+
+    tokio::time::advance(Duration::from_millis(150)).await;
+    stale_trial.await.unwrap().unwrap();
+    assert_eq!(breaker.state(), CircuitState::HalfOpen);
+
+    tokio::time::advance(Duration::from_millis(150)).await;
+    current_trial.await.unwrap().unwrap();
+    assert_eq!(breaker.state(), CircuitState::Closed);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_token_bucket() {
+    let limiter = TokenBucket::new(20, Duration::from_secs(1), 3);
+    assert_eq!(limiter.capacity(), 3);
+    assert_eq!(limiter.available(), 3);
+    assert!(limiter.try_acquire(3));
+    assert!(!limiter.try_acquire(1));
+
+    assert_eq!(limiter.check(1), Err(Duration::from_millis(50)));
+    assert_eq!(limiter.check(4), Err(Duration::MAX));
+
+    let start = tokio::time::Instant::now();
+    limiter.acquire(2).await.unwrap();
+    assert_eq!(start.elapsed(), Duration::from_millis(100));
+    assert!(limiter.acquire(4).await.is_err());
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_gcra_spacing() {
+    let limiter = Gcra::new(50, Duration::from_secs(1), 2);
+    assert!(limiter.try_acquire(1));
+    assert!(limiter.try_acquire(1));
+    assert!(!limiter.try_acquire(1));
+
+    let start = tokio::time::Instant::now();
+    for _ in 0..5 {
+        limiter.acquire(1).await.unwrap();
+    }
+    // Five more permits at 20ms spacing
+    assert_eq!(start.elapsed(), Duration::from_millis(100));
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_sliding_window_log() {
+    let limiter = SlidingWindowLog::new(3, Duration::from_millis(60));
+    assert!(limiter.try_acquire(2));
+    sleep(Duration::from_millis(30)).await;
+    assert!(limiter.try_acquire(1));
+    assert!(!limiter.try_acquire(1));
+
+    // The first two permits leave the window 30ms later
+    assert_eq!(limiter.check(2), Err(Duration::from_millis(30)));
+    let start = tokio::time::Instant::now();
+    limiter.acquire(2).await.unwrap();
+    assert_eq!(start.elapsed(), Duration::from_millis(30));
+    assert!(!limiter.try_acquire(1));
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_rate_limiter_shapes_concurrent_callers() {
+    let limiter = Arc::new(TokenBucket::new(100, Duration::from_secs(1), 1));
+    let start = tokio::time::Instant::now();
+    let handles: Vec<_> = (0..6)
+        .map(|_| {
+            let limiter = limiter.clone();
+            tokio::spawn(async move { limiter.acquire(1).await })
+        })
+        .collect();
+    for handle in handles {
+        handle.await.unwrap().unwrap();
+    }
+    // One immediate permit, then one every 10ms
+    assert_eq!(start.elapsed(), Duration::from_millis(50));
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_keyed_rate_limiter() {
+    let limiter = KeyedRateLimiter::new(Duration::from_millis(50), || {
+        SlidingWindowLog::new(2, Duration::from_millis(40))
+    });
+    assert!(limiter.is_empty());
+
+    assert!(limiter.try_acquire(&"alice", 2));
+    assert!(!limiter.try_acquire(&"alice", 1));
+    assert!(limiter.try_acquire(&"bob", 1));
+    assert_eq!(limiter.len(), 2);
+    assert!(limiter.check(&"alice", 1).is_err());
+
+    limiter.acquire(&"alice", 1).await.unwrap();
+
+    sleep(Duration::from_millis(60)).await;
+    assert_eq!(limiter.evict_idle(), 2);
+    assert!(limiter.is_empty());
+
+    // Eviction also happens while serving other keys
+    assert!(limiter.try_acquire(&"carol", 1));
+    sleep(Duration::from_millis(60)).await;
+    assert!(limiter.try_acquire(&"dave", 1));
+    assert_eq!(limiter.len(), 1);
+}
+
+#[test]
+fn test_memoized_lru_eviction() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let calls_clone = calls.clone();
+    let double = Memoized::new(
+        MemoizeOptions {
+            capacity: 2,
+            ttl: None,
+        },
+        move |n: &u32| {
+            calls_clone.fetch_add(1, Ordering::SeqCst);
+            n * 2
+        },
+    );
+
+    assert_eq!(double.call(1), 2);
+    assert_eq!(double.call(2), 4);
+    assert_eq!(double.call(1), 2);
+    // 2 is the least recently used entry
+    assert_eq!(double.call(3), 6);
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+    assert_eq!(double.call(1), 2);
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+    assert_eq!(double.call(2), 4);
+    assert_eq!(calls.load(Ordering::SeqCst), 4);
+
+    let stats = double.stats();
+    assert_eq!(stats.hits, 2);
+    assert_eq!(stats.misses, 4);
+    assert_eq!(stats.evictions, 2);
+    assert_eq!(stats.size, 2);
+    assert!((stats.hit_rate() - 2.0 / 6.0).abs() < f64::EPSILON);
+}
+
+#[test]
+fn test_memoized_invalidate_and_clear() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let calls_clone = calls.clone();
+    let length = Memoized::new(MemoizeOptions::default(), move |s: &String| {
+        calls_clone.fetch_add(1, Ordering::SeqCst);
+        s.len()
+    });
+
+    assert_eq!(length.call("abc".to_string()), 3);
+    assert!(length.invalidate(&"abc".to_string()));
+    assert!(!length.invalidate(&"abc".to_string()));
+    assert_eq!(length.call("abc".to_string()), 3);
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+    length.call("de".to_string());
+    length.clear();
+    assert_eq!(length.stats().size, 0);
+    length.call("de".to_string());
+    assert_eq!(calls.load(Ordering::SeqCst), 4);
+}
+
+#[test]
+fn test_memoized_skips_insert_invalidated_during_call() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let calls_clone = calls.clone();
+    let barrier = Arc::new(std::sync::Barrier::new(2));
+    let barrier_clone = barrier.clone();
+    let memoized = Memoized::new(MemoizeOptions::default(), move |n: &u32| {
+        // The first call pauses so the test can invalidate while it runs
+        let call = calls_clone.fetch_add(1, Ordering::SeqCst);
+        if call == 0 {
+            barrier_clone.wait();
+            barrier_clone.wait();
+        }
+        n * 10 + call as u32
+    });
+
+    std::thread::scope(|scope| {
+        let handle = scope.spawn(|| memoized.call(1));
+        barrier.wait();
+        memoized.invalidate(&1);
+        barrier.wait();
+        assert_eq!(handle.join().unwrap(), 10);
+    });
+    assert_eq!(memoized.stats().size, 0);
+    assert_eq!(memoized.call(1), 11);
+
+    std::thread::scope(|scope| {
+        calls.store(0, Ordering::SeqCst);
+        let handle = scope.spawn(|| memoized.call(2));
+        barrier.wait();
+        memoized.clear();
+        barrier.wait();
+        assert_eq!(handle.join().unwrap(), 20);
+    });
+    assert_eq!(memoized.stats().size, 0);
+    assert_eq!(memoized.call(2), 21);
+    assert_eq!(memoized.call(2), 21);
+}
+
+#[test]
+fn test_memoized_ttl() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let calls_clone = calls.clone();
+    let memoized = Memoized::new(
+        MemoizeOptions {
+            capacity: 10,
+            ttl: Some(Duration::from_millis(30)),
+        },
+        move |_: &()| calls_clone.fetch_add(1, Ordering::SeqCst),
+    );
+
+    assert_eq!(memoized.call(()), 0);
+    assert_eq!(memoized.call(()), 0);
+    std::thread::sleep(Duration::from_millis(50));
+    assert_eq!(memoized.call(()), 1);
+
+    let stats = memoized.stats();
+    assert_eq!(stats.expirations, 1);
+    assert_eq!(stats.misses, 2);
+}
+
+#[tokio::test]
+async fn test_async_memoized_single_flight() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let calls_clone = calls.clone();
+    let lookup = AsyncMemoized::new(MemoizeOptions::default(), move |id: u32| {
+        let calls = calls_clone.clone();
+        async move {
+            calls.fetch_add(1, Ordering::SeqCst);
+            sleep(Duration::from_millis(30)).await;
+            id * 10
+        }
+    });
+
+    let handles: Vec<_> = (0..5)
+        .map(|_| {
+            let lookup = lookup.clone();
+            tokio::spawn(async move { lookup.call(7).await })
+        })
+        .collect();
+    for handle in handles {
+        assert_eq!(handle.await.unwrap(), 70);
+    }
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+    assert_eq!(lookup.call(7).await, 70);
+    assert_eq!(lookup.call(8).await, 80);
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+    let stats = lookup.stats();
+    assert_eq!(stats.misses, 2);
+    assert_eq!(stats.coalesced, 4);
+    assert_eq!(stats.hits, 1);
+    assert_eq!(stats.size, 2);
+}
+
+#[tokio::test]
+async fn test_async_memoized_cancelled_leader() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let calls_clone = calls.clone();
+    let lookup = AsyncMemoized::new(MemoizeOptions::default(), move |id: u32| {
+        let calls = calls_clone.clone();
+        async move {
+            calls.fetch_add(1, Ordering::SeqCst);
+            sleep(Duration::from_millis(30)).await;
+            id + 1
+        }
+    });
+
+    let leader = tokio::spawn({
+        let lookup = lookup.clone();
+        async move { lookup.call(1).await }
+    });
+    sleep(Duration::from_millis(5)).await;
+    let follower = tokio::spawn({
+        let lookup = lookup.clone();
+        async move { lookup.call(1).await }
+    });
+    sleep(Duration::from_millis(5)).await;
+    leader.abort();
+
+    // The waiting caller takes over the computation
+    assert_eq!(follower.await.unwrap(), 2);
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+    assert_eq!(lookup.call(1).await, 2);
+    assert_eq!(lookup.stats().hits, 1);
+}
+
+#[tokio::test]
+async fn test_async_memoized_invalidate_in_flight() {
+    let lookup = AsyncMemoized::new(MemoizeOptions::default(), |id: u32| async move {
+        sleep(Duration::from_millis(20)).await;
+        id
+    });
+
+    let pending = tokio::spawn({
+        let lookup = lookup.clone();
+        async move { lookup.call(3).await }
+    });
+    sleep(Duration::from_millis(5)).await;
+    assert!(lookup.invalidate(&3));
+    assert_eq!(pending.await.unwrap(), 3);
+
+    // The invalidated result was not cached
+    assert_eq!(lookup.stats().size, 0);
+}
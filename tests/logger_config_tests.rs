@@ -0,0 +1,185 @@
+//! Integration tests for declarative logger configuration
+
+mod common;
+
+use common::temp_dir;
+use mudssky_utils::logger::*;
+use std::fs;
+
+#[test]
+fn test_config_from_json_builds_and_registers() {
+    let dir = temp_dir("config_json");
+    let log_path = dir.join("errors.log");
+    let json = format!(
+        r#"{{
+            "level": "debug",
+            "filter": "cfg_json::db=trace",
+            "loggers": [
+                {{
+                    "name": "cfg_json",
+                    "formatter": {{ "type": "pattern", "pattern": "%l|%m" }},
+                    "outputs": [
+                        {{ "type": "file", "path": {path:?}, "level": "error", "formatter": {{ "type": "json" }} }},
+                        {{ "type": "file", "path": {path:?} }}
+                    ]
+                }},
+                {{ "name": "cfg_json::db", "level": "warn", "redact": true }}
+            ]
+        }}"#,
+        path = log_path.to_string_lossy()
+    );
+
+    let loggers = LoggingConfig::from_json_str(&json).unwrap().apply().unwrap();
+    assert_eq!(loggers.len(), 2);
+    assert_eq!(loggers[0].level(), LogLevel::Debug);
+    // The filter overrides the configured level
+    assert_eq!(get_logger("cfg_json::db").level(), LogLevel::Trace);
+
+    let logger = get_logger("cfg_json");
+    logger.info("hello");
+    logger.error("boom");
+
+    let content = fs::read_to_string(&log_path).unwrap();
+    let lines: Vec<&str> = content.lines().collect();
+    assert_eq!(lines[0], "INFO|hello");
+    assert!(lines[1].starts_with('{'));
+    assert_eq!(lines[2], "ERROR|boom");
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_config_reports_errors_with_keys() {
+    let err = LoggingConfig::from_json_str(r#"{"loggers": [{"name": "a", "level": "loud"}]}"#)
+        .unwrap()
+        .apply()
+        .unwrap_err();
+    assert_eq!(err.key(), "loggers[0].level");
+
+    let err = LoggingConfig::from_json_str(
+        r#"{"loggers": [{"name": "a", "outputs": [{"type": "console", "formatter": {"type": "pattern", "pattern": "%q"}}]}]}"#,
+    )
+    .unwrap()
+    .build()
+    .unwrap_err();
+    assert_eq!(err.key(), "loggers[0].outputs[0].formatter.pattern");
+
+    let err = LoggingConfig::from_json_str(r#"{"filter": "a=nope"}"#)
+        .unwrap()
+        .build()
+        .unwrap_err();
+    assert_eq!(err.key(), "filter");
+
+    let err = LoggingConfig::from_json_str(
+        r#"{"loggers": [{"name": "a", "outputs": [{"type": "file", "path": "a.log", "archive_pattern": "{stem}.{date}{ext}"}]}]}"#,
+    )
+    .unwrap()
+    .validate()
+    .unwrap_err();
+    assert_eq!(err.key(), "loggers[0].outputs[0].archive_pattern");
+
+    let err = LoggingConfig::from_json_str(
+        r#"{"loggers": [{"name": "a", "formatter": {"type": "xml"}}]}"#,
+    )
+    .unwrap_err();
+    assert_eq!(err.key(), "<json>");
+
+    assert!(LoggingConfig::from_json_file("/nonexistent/mudssky.json").is_err());
+}
+
+#[test]
+fn test_config_validates_before_creating_outputs() {
+    let dir = temp_dir("config_validate");
+    let log_dir = dir.join("logs");
+    let json = format!(
+        r#"{{
+            "loggers": [
+                {{ "name": "cfg_validate", "outputs": [{{ "type": "file", "path": {path:?} }}] }},
+                {{ "name": "cfg_validate::db", "level": "loud" }}
+            ]
+        }}"#,
+        path = log_dir.join("app.log").to_string_lossy()
+    );
+    let config = LoggingConfig::from_json_str(&json).unwrap();
+
+    assert_eq!(config.validate().unwrap_err().key(), "loggers[1].level");
+    assert_eq!(config.build().unwrap_err().key(), "loggers[1].level");
+    assert!(!log_dir.exists());
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_config_file_max_size_null_disables_rotation() {
+    let config = LoggingConfig::from_json_str(
+        r#"{"loggers": [{"name": "a", "outputs": [
+            {"type": "file", "path": "a.log"},
+            {"type": "file", "path": "b.log", "max_size": null},
+            {"type": "file", "path": "c.log", "max_size": 100}
+        ]}]}"#,
+    )
+    .unwrap();
+
+    let sizes: Vec<_> = config.loggers[0]
+        .outputs
+        .iter()
+        .map(|output| match &output.kind {
+            OutputKind::File { max_size, .. } => *max_size,
+            other => panic!("unexpected output {other:?}"),
+        })
+        .collect();
+    assert_eq!(sizes, vec![None, Some(None), Some(Some(100))]);
+}
+
+#[test]
+fn test_config_from_env_vars() {
+    let vars = vec![
+        ("MUDSSKY_LOG_NAME".to_string(), "cfg_env".to_string()),
+        ("MUDSSKY_LOG_LEVEL".to_string(), "warn".to_string()),
+        ("MUDSSKY_LOG_FORMAT".to_string(), "json".to_string()),
+        ("MUDSSKY_LOG_REDACT".to_string(), "true".to_string()),
+        ("UNRELATED".to_string(), "x".to_string()),
+    ];
+    let config = LoggingConfig::from_vars(vars).unwrap();
+    assert_eq!(config.loggers.len(), 1);
+    assert_eq!(config.loggers[0].name, "cfg_env");
+    assert_eq!(config.loggers[0].formatter, FormatterSpec::Json);
+    assert!(config.loggers[0].redact);
+
+    let configs = config.build().unwrap();
+    assert_eq!(configs[0].level, LogLevel::Warn);
+    assert!(configs[0].redactor.is_some());
+
+    assert!(LoggingConfig::from_vars(Vec::new()).unwrap().loggers.is_empty());
+
+    let err = LoggingConfig::from_vars(vec![("MUDSSKY_LOG_FORMAT".to_string(), "xml".to_string())])
+        .unwrap_err();
+    assert_eq!(err.key(), "MUDSSKY_LOG_FORMAT");
+
+    for (var, value) in [
+        ("MUDSSKY_LOG_LEVEL", "loud"),
+        ("MUDSSKY_LOG_FILTER", "a=nope"),
+        ("MUDSSKY_LOG_PATTERN", "%q"),
+        ("MUDSSKY_LOG_NAME", ""),
+        ("MUDSSKY_LOG_FILE", ""),
+    ] {
+        let err = LoggingConfig::from_vars(vec![(var.to_string(), value.to_string())]).unwrap_err();
+        assert_eq!(err.key(), var);
+    }
+}
+
+#[test]
+fn test_config_from_env_file_reference() {
+    let dir = temp_dir("config_env_file");
+    let path = dir.join("logging.json");
+    fs::write(&path, r#"{"loggers": [{"name": "cfg_file"}]}"#).unwrap();
+
+    let config = LoggingConfig::from_vars(vec![(
+        "MUDSSKY_LOG_CONFIG".to_string(),
+        path.to_string_lossy().to_string(),
+    )])
+    .unwrap();
+    assert_eq!(config.loggers[0].name, "cfg_file");
+
+    fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,195 @@
+//! Integration tests for file log output
+
+mod common;
+
+use common::temp_dir;
+use mudssky_utils::logger::*;
+use std::fs;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+#[test]
+fn test_file_output_appends() {
+    let dir = temp_dir("file_append");
+    let path = dir.join("app.log");
+    let output = Arc::new(FileOutput::new(&path).unwrap());
+    let logger = Logger::new(LoggerConfig::new("app".to_string()).with_output(output));
+
+    logger.info("first");
+    logger.warn("second");
+
+    let content = fs::read_to_string(&path).unwrap();
+    let lines: Vec<&str> = content.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].contains("first"));
+    assert!(lines[1].contains("[WARN]"));
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_file_output_rotates_by_size() {
+    let dir = temp_dir("file_size");
+    let path = dir.join("app.log");
+    let output = FileOutput::with_options(
+        &path,
+        FileOutputOptions {
+            max_size: Some(20),
+            max_files: 10,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+    output.write("0123456789");
+    output.write("abcdefghij");
+    output.write("klmnopqrst");
+
+    let archives = output.archived_files().unwrap();
+    assert_eq!(archives.len(), 2);
+    assert_eq!(fs::read_to_string(&path).unwrap(), "klmnopqrst\n");
+
+    let name = archives[0].file_name().unwrap().to_string_lossy().to_string();
+    assert!(name.starts_with("app."));
+    assert!(name.ends_with(".log"));
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_file_output_keeps_max_files() {
+    let dir = temp_dir("file_prune");
+    let path = dir.join("app.log");
+    let output = FileOutput::with_options(
+        &path,
+        FileOutputOptions {
+            max_size: None,
+            max_files: 2,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+    for i in 0..5 {
+        output.write(&format!("line {i}"));
+        output.rotate().unwrap();
+    }
+
+    assert_eq!(output.archived_files().unwrap().len(), 2);
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_file_output_custom_archive_pattern() {
+    let dir = temp_dir("file_pattern");
+    let path = dir.join("service.log");
+    let output = FileOutput::with_options(
+        &path,
+        FileOutputOptions {
+            archive_pattern: "{filename}.{index}".to_string(),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+    output.write("hello");
+    output.rotate().unwrap();
+    output.write("world");
+    output.rotate().unwrap();
+
+    assert!(dir.join("service.log.1").exists());
+    assert!(dir.join("service.log.2").exists());
+    assert_eq!(
+        fs::read_to_string(dir.join("service.log.1")).unwrap(),
+        "hello\n"
+    );
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_file_output_rejects_archive_pattern_without_index() {
+    let dir = temp_dir("file_no_index");
+    let path = dir.join("app.log");
+    let err = FileOutput::with_options(
+        &path,
+        FileOutputOptions {
+            max_size: Some(10),
+            archive_pattern: "{stem}.{date}{ext}".to_string(),
+            ..Default::default()
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    assert!(!path.exists());
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_file_output_size_rotation_keeps_every_archive() {
+    let dir = temp_dir("file_keep_all");
+    let path = dir.join("app.log");
+    let output = FileOutput::with_options(
+        &path,
+        FileOutputOptions {
+            max_size: Some(10),
+            max_files: 10,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+    output.write("first-line");
+    output.write("second-line");
+    output.write("third-line");
+
+    let mut contents: Vec<String> = output
+        .archived_files()
+        .unwrap()
+        .iter()
+        .map(|archive| fs::read_to_string(archive).unwrap())
+        .collect();
+    contents.sort();
+    assert_eq!(contents, ["first-line\n", "second-line\n"]);
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_file_output_daily_rotation_uses_existing_file_date() {
+    let dir = temp_dir("file_reopen");
+    let path = dir.join("app.log");
+    fs::write(&path, "yesterday\n").unwrap();
+    let two_days_ago = SystemTime::now() - Duration::from_secs(2 * 24 * 60 * 60);
+    fs::File::options()
+        .write(true)
+        .open(&path)
+        .unwrap()
+        .set_modified(two_days_ago)
+        .unwrap();
+
+    let output = FileOutput::with_options(
+        &path,
+        FileOutputOptions {
+            max_size: None,
+            rotate_daily: true,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    output.write("today");
+
+    let archives = output.archived_files().unwrap();
+    assert_eq!(archives.len(), 1);
+    let date = chrono::DateTime::<chrono::Utc>::from(two_days_ago).format("%Y-%m-%d");
+    assert_eq!(
+        archives[0].file_name().unwrap().to_string_lossy(),
+        format!("app.{date}.1.log")
+    );
+    assert_eq!(fs::read_to_string(&archives[0]).unwrap(), "yesterday\n");
+    assert_eq!(fs::read_to_string(&path).unwrap(), "today\n");
+
+    fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,106 @@
+//! Integration tests for the `log` crate bridge
+
+mod common;
+
+use common::TestOutput;
+use mudssky_utils::logger::*;
+use serde_json::Value;
+use std::sync::{Arc, Mutex, Once};
+
+/// The bridge, filter and fallback are global, so tests touching them run one at a time
+static BRIDGE_LOCK: Mutex<()> = Mutex::new(());
+
+fn install_bridge() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| init_log_bridge().unwrap());
+}
+
+#[test]
+fn test_log_bridge_routes_records() {
+    let _lock = BRIDGE_LOCK.lock().unwrap();
+    let output = TestOutput::new();
+    create_logger(
+        LoggerConfig::new("bridge".to_string())
+            .with_level(LogLevel::Debug)
+            .with_formatter(Arc::new(JsonFormatter))
+            .with_output(Arc::new(output.clone())),
+    );
+
+    install_bridge();
+    assert!(init_log_bridge().is_err());
+
+    log::debug!(target: "bridge::db", "connected to {}", "sqlite");
+    log::trace!(target: "bridge", "filtered out");
+    assert!(log::log_enabled!(target: "bridge::db", log::Level::Debug));
+    assert!(!log::log_enabled!(target: "bridge::db", log::Level::Trace));
+
+    let messages = output.get_messages();
+    assert_eq!(messages.len(), 1);
+
+    let parsed: Value = serde_json::from_str(&messages[0]).unwrap();
+    assert_eq!(parsed["level"], "DEBUG");
+    assert_eq!(parsed["logger"], "bridge::db");
+    assert_eq!(parsed["message"], "connected to sqlite");
+    assert_eq!(parsed["module_path"], module_path!());
+    assert!(parsed["file"].as_str().unwrap().ends_with("logger_log_bridge_tests.rs"));
+    assert!(parsed["line"].as_u64().unwrap() > 0);
+}
+
+#[test]
+fn test_log_bridge_never_registers_loggers() {
+    let _lock = BRIDGE_LOCK.lock().unwrap();
+    install_bridge();
+    let before = logger_names();
+
+    assert!(!log::log_enabled!(target: "thirdparty::proto::h1", log::Level::Error));
+    log::error!(target: "thirdparty::proto::h1", "dropped");
+    assert_eq!(logger_names(), before);
+
+    // Unmatched targets go to the fallback, which stays unregistered
+    let output = TestOutput::new();
+    let fallback = Logger::new(
+        LoggerConfig::new("fallback".to_string())
+            .with_level(LogLevel::Warn)
+            .with_output(Arc::new(output.clone())),
+    );
+    set_log_bridge_fallback(Some(fallback));
+    assert!(log::log_enabled!(target: "thirdparty::proto::h1", log::Level::Warn));
+    assert!(!log::log_enabled!(target: "thirdparty::proto::h1", log::Level::Info));
+    log::warn!(target: "thirdparty::proto::h1", "slow response");
+    set_log_bridge_fallback(None);
+
+    assert_eq!(logger_names(), before);
+    let messages = output.get_messages();
+    assert_eq!(messages.len(), 1);
+    assert!(messages[0].contains("slow response"));
+}
+
+#[test]
+fn test_log_bridge_applies_filter_to_exact_target() {
+    let _lock = BRIDGE_LOCK.lock().unwrap();
+    install_bridge();
+    let output = TestOutput::new();
+    create_logger(
+        LoggerConfig::new("filtered".to_string())
+            .with_level(LogLevel::Info)
+            .with_output(Arc::new(output.clone())),
+    );
+
+    set_filter_spec("filtered=info,filtered::noisy=error,filtered::chatty=debug").unwrap();
+    log::warn!(target: "filtered::noisy", "hidden");
+    log::error!(target: "filtered::noisy", "shown");
+    log::debug!(target: "filtered::chatty", "verbose");
+    log::debug!(target: "filtered::other", "hidden");
+    clear_filter();
+
+    let messages = output.get_messages();
+    assert_eq!(messages.len(), 2);
+    assert!(messages[0].contains("shown"));
+    assert!(messages[1].contains("verbose"));
+}
+
+#[test]
+fn test_log_level_conversion() {
+    assert_eq!(LogLevel::from(log::Level::Warn), LogLevel::Warn);
+    assert_eq!(log::Level::from(LogLevel::Trace), log::Level::Trace);
+}
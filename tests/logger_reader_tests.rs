@@ -0,0 +1,214 @@
+//! Integration tests for reading JSON log files
+
+mod common;
+
+use chrono::{TimeZone, Utc};
+use common::temp_dir;
+use mudssky_utils::logger::*;
+use serde_json::json;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+fn json_line(second: u32, level: &str, logger: &str, message: &str) -> String {
+    format!(
+        r#"{{"timestamp":"2024-05-01T10:00:{second:02}+00:00","level":"{level}","logger":"{logger}","message":"{message}","request_id":{second}}}"#
+    )
+}
+
+fn append(path: &PathBuf, text: &str) {
+    let mut file = OpenOptions::new().create(true).append(true).open(path).unwrap();
+    file.write_all(text.as_bytes()).unwrap();
+}
+
+#[test]
+fn test_parse_json_line_round_trip() {
+    let entry = LogEntry::new(
+        LogLevel::Error,
+        "db::pool".to_string(),
+        "timeout".to_string(),
+    )
+    .with_metadata("retries".to_string(), json!(3))
+    .with_metadata("tags".to_string(), json!(["a", "b"]));
+    let line = JsonFormatter.format(&entry);
+
+    let parsed = parse_json_line(&line).unwrap();
+    assert_eq!(parsed.timestamp, entry.timestamp);
+    assert_eq!(parsed.level, LogLevel::Error);
+    assert_eq!(parsed.logger_name, "db::pool");
+    assert_eq!(parsed.message, "timeout");
+    assert_eq!(parsed.metadata, entry.metadata);
+}
+
+#[test]
+fn test_parse_json_line_errors() {
+    assert!(parse_json_line("not json").is_err());
+    assert!(parse_json_line("[1, 2]").is_err());
+    assert!(parse_json_line(r#"{"level":"INFO","logger":"a","message":"m"}"#).is_err());
+
+    let bad_level = parse_json_line(
+        r#"{"timestamp":"2024-05-01T10:00:00Z","level":"LOUD","logger":"a","message":"m"}"#,
+    )
+    .unwrap_err();
+    assert_eq!(bad_level.input(), "LOUD");
+    assert_eq!(bad_level.expected(), "log level");
+}
+
+#[test]
+fn test_reader_skips_malformed_lines() {
+    let mut log = String::new();
+    log.push_str(&json_line(1, "INFO", "app", "started"));
+    log.push_str("\n\n{broken\n");
+    log.push_str(&json_line(2, "WARN", "db", "slow"));
+    log.push('\n');
+
+    let mut reader = LogReader::new(log.as_bytes());
+    let messages: Vec<String> = reader.by_ref().map(|e| e.message).collect();
+    assert_eq!(messages, vec!["started", "slow"]);
+    assert_eq!(reader.malformed_count(), 1);
+    assert_eq!(reader.line_number(), 4);
+    assert!(reader.io_error().is_none());
+}
+
+#[test]
+fn test_read_json_log_with_query() {
+    let dir = temp_dir("reader_query");
+    let path = dir.join("app.log");
+    let lines: Vec<String> = vec![
+        json_line(1, "INFO", "app", "started"),
+        json_line(5, "ERROR", "db", "connection lost"),
+        json_line(9, "WARN", "db", "slow query"),
+        json_line(20, "ERROR", "db", "connection lost again"),
+    ];
+    fs::write(&path, lines.join("\n") + "\n").unwrap();
+
+    let since = Utc.with_ymd_and_hms(2024, 5, 1, 10, 0, 5).unwrap();
+    let until = Utc.with_ymd_and_hms(2024, 5, 1, 10, 0, 20).unwrap();
+    let query = LogQuery::new().logger("db").since(since).until(until);
+    let entries = read_json_log(&path, &query).unwrap();
+    let messages: Vec<&str> = entries.iter().map(|e| e.message.as_str()).collect();
+    assert_eq!(messages, vec!["connection lost", "slow query"]);
+
+    let query = LogQuery::new()
+        .level(LogLevel::Error)
+        .metadata_matches("request_id", |v| v.as_u64().is_some_and(|id| id > 10));
+    let entries = read_json_log(&path, &query).unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].message, "connection lost again");
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_query_filter_and_display() {
+    let query = LogQuery::new()
+        .since(Utc.with_ymd_and_hms(2024, 5, 1, 0, 0, 0).unwrap())
+        .filter(|e| e.message.len() > 3);
+    assert_eq!(
+        query.to_string(),
+        "timestamp >= 2024-05-01T00:00:00+00:00 && <predicate>"
+    );
+
+    let entry = LogEntry::new(LogLevel::Info, "app".to_string(), "long".to_string());
+    assert!(query.matches(&entry));
+    let entry = LogEntry::new(LogLevel::Info, "app".to_string(), "x".to_string());
+    assert!(!query.matches(&entry));
+}
+
+#[test]
+fn test_tail_follows_appends_and_partial_lines() {
+    let dir = temp_dir("reader_tail");
+    let path = dir.join("app.log");
+    append(
+        &path,
+        &format!("{}\n", json_line(1, "INFO", "app", "before tail")),
+    );
+
+    let mut tail = LogTail::open(&path).unwrap();
+    assert!(tail.poll().unwrap().is_empty());
+
+    let line = json_line(2, "INFO", "app", "appended");
+    let (head, rest) = line.split_at(20);
+    append(&path, head);
+    assert!(tail.poll().unwrap().is_empty());
+    append(&path, &format!("{rest}\nnot json\n"));
+    let entries = tail.poll().unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].message, "appended");
+    assert_eq!(tail.malformed_count(), 1);
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_tail_follows_rotation() {
+    let dir = temp_dir("reader_tail_rotate");
+    let path = dir.join("app.log");
+    let output = Arc::new(FileOutput::new(&path).unwrap());
+    let logger = Logger::new(
+        LoggerConfig::new("app".to_string())
+            .with_formatter(Arc::new(JsonFormatter))
+            .with_output(output.clone()),
+    );
+
+    let mut tail = LogTail::from_start(&path)
+        .unwrap()
+        .with_query(LogQuery::new().min_level(LogLevel::Info));
+    logger.info("one");
+    logger.debug("hidden");
+    assert_eq!(tail.poll().unwrap().len(), 1);
+
+    logger.info("two");
+    output.rotate().unwrap();
+    logger.info("three");
+
+    let messages: Vec<String> = tail.poll().unwrap().into_iter().map(|e| e.message).collect();
+    assert_eq!(messages, vec!["two", "three"]);
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_tail_handles_truncation_and_missing_file() {
+    let dir = temp_dir("reader_tail_truncate");
+    let path = dir.join("app.log");
+
+    let mut tail = LogTail::open(&path).unwrap();
+    assert!(tail.poll().unwrap().is_empty());
+
+    append(
+        &path,
+        &format!("{}\n", json_line(1, "INFO", "app", "created")),
+    );
+    assert_eq!(tail.poll().unwrap()[0].message, "created");
+
+    fs::write(&path, format!("{}\n", json_line(2, "INFO", "app", "fresh"))).unwrap();
+    let entries = tail.poll().unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].message, "fresh");
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn test_tail_next_batch() {
+    let dir = temp_dir("reader_tail_async");
+    let path = dir.join("app.log");
+    append(&path, "");
+    let mut tail = LogTail::open(&path).unwrap();
+
+    let writer_path = path.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(std::time::Duration::from_millis(30)).await;
+        append(
+            &writer_path,
+            &format!("{}\n", json_line(3, "INFO", "app", "late")),
+        );
+    });
+
+    let entries = tail.next_batch(std::time::Duration::from_millis(5)).await.unwrap();
+    assert_eq!(entries[0].message, "late");
+
+    let _ = fs::remove_dir_all(&dir);
+}
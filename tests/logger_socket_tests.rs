@@ -0,0 +1,191 @@
+//! Integration tests for socket log output
+
+use mudssky_utils::logger::*;
+use std::io::{BufRead, BufReader, Read};
+use std::net::{TcpListener, UdpSocket};
+use std::sync::Arc;
+use std::time::Duration;
+
+fn fast_options(framing: Framing) -> SocketOutputOptions {
+    SocketOutputOptions {
+        framing,
+        reconnect_delay: Duration::ZERO,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_framing() {
+    assert_eq!(Framing::Newline.frame("hello"), b"hello\n");
+    assert_eq!(Framing::OctetCounting.frame("héllo"), "6 héllo".as_bytes());
+}
+
+#[test]
+fn test_udp_output_sends_one_datagram_per_message() {
+    let receiver = UdpSocket::bind("127.0.0.1:0").unwrap();
+    receiver.set_read_timeout(Some(Duration::from_secs(2))).unwrap();
+    let addr = receiver.local_addr().unwrap().to_string();
+
+    let output = Arc::new(SocketOutput::udp(addr));
+    let logger = Logger::new(
+        LoggerConfig::new("udp".to_string())
+            .with_formatter(Arc::new(JsonFormatter))
+            .with_output(output.clone()),
+    );
+    logger.info("first");
+    logger.info("second");
+
+    let mut buf = [0u8; 2048];
+    let n = receiver.recv(&mut buf).unwrap();
+    let first: serde_json::Value = serde_json::from_slice(&buf[..n]).unwrap();
+    assert_eq!(first["message"], "first");
+    let n = receiver.recv(&mut buf).unwrap();
+    let second: serde_json::Value = serde_json::from_slice(&buf[..n]).unwrap();
+    assert_eq!(second["message"], "second");
+    assert_eq!(output.pending(), 0);
+}
+
+#[test]
+fn test_tcp_output_newline_framing() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap().to_string();
+
+    let output =
+        SocketOutput::with_options(SocketTarget::Tcp(addr), fast_options(Framing::Newline));
+    output.write("line one");
+    output.write("line two");
+    output.flush();
+    assert!(output.is_connected());
+    drop(output);
+
+    let (stream, _) = listener.accept().unwrap();
+    let lines: Vec<String> = BufReader::new(stream).lines().map(|l| l.unwrap()).collect();
+    assert_eq!(lines, vec!["line one", "line two"]);
+}
+
+#[test]
+fn test_tcp_output_octet_counting() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap().to_string();
+
+    let output = SocketOutput::with_options(
+        SocketTarget::Tcp(addr),
+        fast_options(Framing::OctetCounting),
+    );
+    output.write("<14>1 msg");
+    output.write("a b");
+    drop(output);
+
+    let (mut stream, _) = listener.accept().unwrap();
+    let mut received = String::new();
+    stream.read_to_string(&mut received).unwrap();
+    assert_eq!(received, "9 <14>1 msg3 a b");
+}
+
+#[test]
+fn test_tcp_output_buffers_until_collector_is_up() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    drop(listener);
+
+    let options = SocketOutputOptions {
+        buffer_capacity: 2,
+        ..fast_options(Framing::Newline)
+    };
+    let output = SocketOutput::with_options(SocketTarget::Tcp(addr.to_string()), options);
+    output.write("dropped");
+    output.write("kept 1");
+    output.write("kept 2");
+    assert!(!output.is_connected());
+    assert_eq!(output.pending(), 2);
+    assert_eq!(output.dropped_count(), 1);
+    assert!(output.try_flush().is_err());
+
+    let listener = TcpListener::bind(addr).unwrap();
+    output.try_flush().unwrap();
+    assert_eq!(output.pending(), 0);
+    drop(output);
+
+    let (stream, _) = listener.accept().unwrap();
+    let lines: Vec<String> = BufReader::new(stream).lines().map(|l| l.unwrap()).collect();
+    assert_eq!(lines, vec!["kept 1", "kept 2"]);
+}
+
+#[test]
+fn test_reconnect_delay_limits_attempts() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    drop(listener);
+
+    let options = SocketOutputOptions {
+        reconnect_delay: Duration::from_secs(60),
+        ..Default::default()
+    };
+    let output = SocketOutput::with_options(SocketTarget::Tcp(addr.to_string()), options);
+    output.write("first");
+
+    let listener = TcpListener::bind(addr).unwrap();
+    // Still within the reconnect delay, so the message stays buffered
+    output.write("second");
+    assert_eq!(output.pending(), 2);
+
+    // An explicit flush reconnects right away
+    output.try_flush().unwrap();
+    drop(output);
+    let (stream, _) = listener.accept().unwrap();
+    assert_eq!(BufReader::new(stream).lines().count(), 2);
+}
+
+#[test]
+fn test_tcp_output_reconnects_after_collector_drops_connection() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap().to_string();
+
+    let output =
+        SocketOutput::with_options(SocketTarget::Tcp(addr), fast_options(Framing::Newline));
+    output.write("message 0");
+    let (first, _) = listener.accept().unwrap();
+    let mut reader = BufReader::new(first);
+    let mut line = String::new();
+    reader.read_line(&mut line).unwrap();
+    assert_eq!(line, "message 0\n");
+    drop(reader);
+
+    // Writes keep succeeding until the reset from the closed peer arrives
+    let mut sent = 1;
+    while output.is_connected() {
+        assert!(sent < 100, "connection was never reported as broken");
+        std::thread::sleep(Duration::from_millis(10));
+        output.write(&format!("message {sent}"));
+        sent += 1;
+    }
+    // The failed message stays buffered for the next connection
+    assert_eq!(output.pending(), 1);
+
+    output.try_flush().unwrap();
+    assert_eq!(output.pending(), 0);
+    drop(output);
+
+    let (second, _) = listener.accept().unwrap();
+    let lines: Vec<String> = BufReader::new(second).lines().map(|l| l.unwrap()).collect();
+    assert_eq!(lines, vec![format!("message {}", sent - 1)]);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_unix_output() {
+    use std::os::unix::net::UnixListener;
+
+    let path = std::env::temp_dir().join(format!("mudssky_utils_log_{}.sock", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let listener = UnixListener::bind(&path).unwrap();
+
+    let output = SocketOutput::unix(&path);
+    output.write("over unix");
+    drop(output);
+
+    let (stream, _) = listener.accept().unwrap();
+    let lines: Vec<String> = BufReader::new(stream).lines().map(|l| l.unwrap()).collect();
+    assert_eq!(lines, vec!["over unix"]);
+    let _ = std::fs::remove_file(&path);
+}
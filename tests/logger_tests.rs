@@ -1,33 +1,13 @@
 //! Integration tests for logger utilities
 
+mod common;
+
+use common::TestOutput;
 use mudssky_utils::logger::*;
 use serde_json::{Value, json};
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 
-#[derive(Debug, Clone)]
-struct TestOutput {
-    messages: Arc<Mutex<Vec<String>>>,
-}
-
-impl TestOutput {
-    fn new() -> Self {
-        Self {
-            messages: Arc::new(Mutex::new(Vec::new())),
-        }
-    }
-
-    fn get_messages(&self) -> Vec<String> {
-        self.messages.lock().unwrap().clone()
-    }
-}
-
-impl LogOutput for TestOutput {
-    fn write(&self, formatted_message: &str) {
-        self.messages.lock().unwrap().push(formatted_message.to_string());
-    }
-}
-
 #[test]
 fn test_log_level_ordering() {
     assert!(LogLevel::Trace < LogLevel::Debug);
@@ -127,3 +107,1053 @@ fn test_log_entry_with_metadata() {
     assert_eq!(entry.metadata["key1"], json!("value1"));
     assert_eq!(entry.metadata["key2"], json!(42));
 }
+
+/// Output that blocks until the gate is opened
+struct GatedOutput {
+    gate: Arc<Mutex<()>>,
+    inner: TestOutput,
+}
+
+impl LogOutput for GatedOutput {
+    fn write(&self, formatted_message: &str) {
+        let _open = self.gate.lock().unwrap();
+        self.inner.write(formatted_message);
+    }
+}
+
+#[test]
+fn test_async_output_flush() {
+    let test_output = TestOutput::new();
+    let output = Arc::new(AsyncOutput::new(Arc::new(test_output.clone())));
+    let logger = Logger::new(LoggerConfig::new("async".to_string()).with_output(output.clone()));
+
+    for i in 0..100 {
+        logger.info(&format!("message {i}"));
+    }
+    output.flush();
+
+    let messages = test_output.get_messages();
+    assert_eq!(messages.len(), 100);
+    assert!(messages[99].contains("message 99"));
+}
+
+#[test]
+fn test_async_output_drop_oldest() {
+    let gate = Arc::new(Mutex::new(()));
+    let test_output = TestOutput::new();
+    let output = AsyncOutput::with_options(
+        Arc::new(GatedOutput {
+            gate: gate.clone(),
+            inner: test_output.clone(),
+        }),
+        AsyncOutputOptions {
+            capacity: 2,
+            overflow: OverflowPolicy::DropOldest,
+        },
+    );
+
+    let closed = gate.lock().unwrap();
+    output.write("a");
+    // Wait until the worker is stuck writing "a"
+    while output.pending() > 0 {
+        std::thread::yield_now();
+    }
+    for line in ["b", "c", "d", "e"] {
+        output.write(line);
+    }
+    drop(closed);
+    output.flush();
+
+    let messages = test_output.get_messages();
+    assert_eq!(output.dropped_count(), 2);
+    assert_eq!(messages[0], "a");
+    assert!(messages[1].contains("dropped 2"));
+    assert_eq!(&messages[2..], &["d".to_string(), "e".to_string()]);
+}
+
+#[test]
+fn test_async_output_drop_newest() {
+    let gate = Arc::new(Mutex::new(()));
+    let test_output = TestOutput::new();
+    let output = AsyncOutput::with_options(
+        Arc::new(GatedOutput {
+            gate: gate.clone(),
+            inner: test_output.clone(),
+        }),
+        AsyncOutputOptions {
+            capacity: 1,
+            overflow: OverflowPolicy::DropNewest,
+        },
+    );
+
+    let closed = gate.lock().unwrap();
+    output.write("a");
+    while output.pending() > 0 {
+        std::thread::yield_now();
+    }
+    for line in ["b", "c", "d"] {
+        output.write(line);
+    }
+    drop(closed);
+    output.flush();
+
+    let messages = test_output.get_messages();
+    assert_eq!(output.dropped_count(), 2);
+    assert!(messages.contains(&"b".to_string()));
+    assert!(!messages.contains(&"d".to_string()));
+}
+
+#[test]
+fn test_async_output_shutdown_guard() {
+    let test_output = TestOutput::new();
+    let output = Arc::new(AsyncOutput::new(Arc::new(test_output.clone())));
+
+    {
+        let _guard = output.shutdown_guard();
+        for i in 0..10 {
+            output.write(&format!("line {i}"));
+        }
+    }
+
+    assert_eq!(test_output.get_messages().len(), 10);
+    output.write("after shutdown");
+    assert_eq!(test_output.get_messages().len(), 11);
+}
+
+#[test]
+fn test_log_filter_parse() {
+    let filter = LogFilter::parse("info, db=debug ,http::client=warn,noisy").unwrap();
+    assert_eq!(filter.default_level(), Some(LogLevel::Info));
+    assert_eq!(filter.directives().len(), 3);
+    assert_eq!(
+        filter.to_string(),
+        "info,db=debug,http::client=warn,noisy=trace"
+    );
+
+    let err = LogFilter::parse("info,db=verbose").unwrap_err();
+    assert_eq!(err.position(), Some(5));
+    assert!(LogFilter::parse("=debug").is_err());
+}
+
+#[test]
+fn test_log_filter_most_specific_match() {
+    let filter: LogFilter = "warn,app=info,app::db=debug,app::db::pool=trace".parse().unwrap();
+
+    assert_eq!(filter.level_for("app"), Some(LogLevel::Info));
+    assert_eq!(filter.level_for("app::http"), Some(LogLevel::Info));
+    assert_eq!(filter.level_for("app::db::query"), Some(LogLevel::Debug));
+    assert_eq!(filter.level_for("app::db::pool"), Some(LogLevel::Trace));
+    assert_eq!(filter.level_for("application"), Some(LogLevel::Warn));
+    assert_eq!(
+        LogFilter::parse("db=debug").unwrap().level_for("other"),
+        None
+    );
+}
+
+/// Serializes tests that change the global filter
+static FILTER_LOCK: Mutex<()> = Mutex::new(());
+
+#[test]
+fn test_set_filter_applies_to_registry() {
+    let _lock = FILTER_LOCK.lock().unwrap();
+    let early = get_logger("filter_test::db");
+    let unrelated = get_logger("filter_test_other");
+    assert_eq!(unrelated.level(), LogLevel::Info);
+
+    set_filter_spec("filter_test=warn,filter_test::db=debug").unwrap();
+    assert_eq!(early.level(), LogLevel::Debug);
+    assert!(early.is_enabled(LogLevel::Debug));
+    assert_eq!(get_logger("filter_test::http").level(), LogLevel::Warn);
+    assert_eq!(unrelated.level(), LogLevel::Info);
+
+    // Re-applying at runtime changes existing loggers
+    set_filter_spec("filter_test=error").unwrap();
+    assert_eq!(early.level(), LogLevel::Error);
+    clear_filter();
+}
+
+#[test]
+fn test_apply_filter_from_env() {
+    let _lock = FILTER_LOCK.lock().unwrap();
+    let var = "MUDSSKY_UTILS_TEST_LOG_FILTER";
+    assert!(!apply_filter_from_env(Some(var)).unwrap());
+
+    // SAFETY: the variable name is unique to this test
+    unsafe { std::env::set_var(var, "env_filter_test=trace") };
+    assert!(apply_filter_from_env(Some(var)).unwrap());
+    assert_eq!(get_logger("env_filter_test::x").level(), LogLevel::Trace);
+    unsafe { std::env::remove_var(var) };
+    clear_filter();
+}
+
+struct CountingDisplay(Arc<Mutex<usize>>);
+
+impl std::fmt::Display for CountingDisplay {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        *self.0.lock().unwrap() += 1;
+        write!(f, "counted")
+    }
+}
+
+#[test]
+fn test_logging_macros_with_fields() {
+    let test_output = Arc::new(TestOutput::new());
+    let logger = Logger::new(
+        LoggerConfig::new("macros".to_string())
+            .with_formatter(Arc::new(JsonFormatter))
+            .with_output(test_output.clone()),
+    );
+
+    let n = 3;
+    mudssky_utils::info!(
+        logger,
+        user_id = 42,
+        tags = vec!["a", "b"],
+        "saved {} rows",
+        n
+    );
+    mudssky_utils::warn!(logger, "plain {n}");
+
+    let messages = test_output.get_messages();
+    assert_eq!(messages.len(), 2);
+
+    let parsed: Value = serde_json::from_str(&messages[0]).unwrap();
+    assert_eq!(parsed["message"], "saved 3 rows");
+    assert_eq!(parsed["level"], "INFO");
+    assert_eq!(parsed["user_id"], 42);
+    assert_eq!(parsed["tags"], json!(["a", "b"]));
+    assert!(parsed["file"].as_str().unwrap().ends_with("logger_tests.rs"));
+    assert!(parsed["line"].as_u64().unwrap() > 0);
+    assert_eq!(parsed["module_path"], "logger_tests");
+
+    let parsed: Value = serde_json::from_str(&messages[1]).unwrap();
+    assert_eq!(parsed["message"], "plain 3");
+}
+
+#[test]
+fn test_logging_macro_fields_override_call_site() {
+    let test_output = Arc::new(TestOutput::new());
+    let logger = Logger::new(
+        LoggerConfig::new("macros_override".to_string())
+            .with_formatter(Arc::new(JsonFormatter))
+            .with_output(test_output.clone()),
+    );
+
+    let path = "/tmp/upload.csv";
+    mudssky_utils::info!(logger, file = path, line = 7, "imported");
+
+    let parsed: Value = serde_json::from_str(&test_output.get_messages()[0]).unwrap();
+    assert_eq!(parsed["file"], path);
+    assert_eq!(parsed["line"], 7);
+    assert_eq!(parsed["module_path"], "logger_tests");
+}
+
+#[test]
+fn test_logging_macros_are_lazy() {
+    let test_output = Arc::new(TestOutput::new());
+    let logger = Logger::new(
+        LoggerConfig::new("lazy".to_string())
+            .with_level(LogLevel::Warn)
+            .with_output(test_output.clone()),
+    );
+    let count = Arc::new(Mutex::new(0));
+
+    mudssky_utils::debug!(logger, "{}", CountingDisplay(count.clone()));
+    mudssky_utils::log!(logger, LogLevel::Info, "{}", CountingDisplay(count.clone()));
+    assert_eq!(*count.lock().unwrap(), 0);
+
+    mudssky_utils::error!(logger, "{}", CountingDisplay(count.clone()));
+    assert_eq!(*count.lock().unwrap(), 1);
+    assert_eq!(test_output.get_messages().len(), 1);
+}
+
+#[test]
+fn test_thread_context_nesting() {
+    let test_output = Arc::new(TestOutput::new());
+    let logger = Logger::new(
+        LoggerConfig::new("ctx".to_string())
+            .with_formatter(Arc::new(JsonFormatter))
+            .with_output(test_output.clone()),
+    );
+
+    {
+        let _outer = push_context([("request_id", json!("r1")), ("tenant", json!("acme"))]);
+        {
+            let _inner = push_context([("tenant", json!("globex"))]);
+            logger.info("inner");
+        }
+        let mut metadata = HashMap::new();
+        metadata.insert("request_id".to_string(), json!("explicit"));
+        logger.log_with_metadata(LogLevel::Info, "outer", metadata);
+    }
+    logger.info("outside");
+
+    let messages: Vec<Value> = test_output
+        .get_messages()
+        .iter()
+        .map(|m| serde_json::from_str(m).unwrap())
+        .collect();
+    assert_eq!(messages[0]["tenant"], "globex");
+    assert_eq!(messages[0]["request_id"], "r1");
+    assert_eq!(messages[1]["tenant"], "acme");
+    assert_eq!(messages[1]["request_id"], "explicit");
+    assert!(messages[2].get("request_id").is_none());
+}
+
+#[test]
+fn test_with_thread_context_removes_fields() {
+    let value = with_thread_context([("user", json!(7))], || current_context()["user"].clone());
+    assert_eq!(value, json!(7));
+    assert!(!current_context().contains_key("user"));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_task_context_across_await() {
+    let test_output = Arc::new(TestOutput::new());
+    let logger = Logger::new(
+        LoggerConfig::new("task_ctx".to_string())
+            .with_formatter(Arc::new(JsonFormatter))
+            .with_output(test_output.clone()),
+    );
+
+    let task_logger = logger.clone();
+    tokio::spawn(with_task_context(
+        [("request_id", json!("t1"))],
+        async move {
+            tokio::task::yield_now().await;
+            with_task_context([("user", json!("bob"))], async {
+                tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+                task_logger.info("nested");
+            })
+            .await;
+            task_logger.info("outer");
+        },
+    ))
+    .await
+    .unwrap();
+    logger.info("no context");
+
+    let messages: Vec<Value> = test_output
+        .get_messages()
+        .iter()
+        .map(|m| serde_json::from_str(m).unwrap())
+        .collect();
+    assert_eq!(messages[0]["request_id"], "t1");
+    assert_eq!(messages[0]["user"], "bob");
+    assert_eq!(messages[1]["request_id"], "t1");
+    assert!(messages[1].get("user").is_none());
+    assert!(messages[2].get("request_id").is_none());
+}
+
+#[test]
+fn test_pattern_formatter_tokens() {
+    let formatter = PatternFormatter::new("%d{%Y-%m-%d %H:%M:%S%.3f} [%l] %n - %m %M").unwrap();
+    let entry = LogEntry::new(
+        LogLevel::Warn,
+        "app::db".to_string(),
+        "slow query".to_string(),
+    )
+    .with_metadata("ms".to_string(), json!(250));
+    let expected = format!(
+        "{} [WARN] app::db - slow query {{\"ms\":250}}",
+        entry.timestamp.format("%Y-%m-%d %H:%M:%S%.3f")
+    );
+    assert_eq!(formatter.format(&entry), expected);
+
+    let formatter = PatternFormatter::new("%m|%M|100%%").unwrap();
+    let entry = LogEntry::new(LogLevel::Info, "app".to_string(), "x".to_string());
+    assert_eq!(formatter.format(&entry), "x||100%");
+}
+
+#[test]
+fn test_pattern_formatter_modifiers() {
+    let formatter = PatternFormatter::new("%-5l|%5l|%.3n|%-8.4n|").unwrap();
+    let entry = LogEntry::new(LogLevel::Info, "database".to_string(), String::new());
+    assert_eq!(formatter.format(&entry), "INFO | INFO|dat|data    |");
+}
+
+#[test]
+fn test_pattern_formatter_thread_tokens() {
+    let formatter = PatternFormatter::new("%t/%T").unwrap();
+    let entry = LogEntry::new(LogLevel::Info, "app".to_string(), String::new());
+    let formatted = std::thread::Builder::new()
+        .name("worker-1".to_string())
+        .spawn(move || formatter.format(&entry))
+        .unwrap()
+        .join()
+        .unwrap();
+
+    let (name, id) = formatted.split_once('/').unwrap();
+    assert_eq!(name, "worker-1");
+    assert!(id.parse::<u64>().is_ok());
+}
+
+#[test]
+fn test_pattern_formatter_validation() {
+    let err = PatternFormatter::new("[%l] %x").unwrap_err();
+    assert_eq!(err.position(), Some(5));
+    assert!(PatternFormatter::new("%d{%Y-%Q}").is_err());
+    assert!(PatternFormatter::new("%d{%Y").is_err());
+    assert!(PatternFormatter::new("%.l").is_err());
+    assert!(PatternFormatter::new("trailing %").is_err());
+
+    let err = PatternFormatter::new("%99999999999999999999m").unwrap_err();
+    assert_eq!(err.position(), Some(1));
+    assert_eq!(
+        PatternFormatter::new("x %.2000m").unwrap_err().position(),
+        Some(4)
+    );
+    assert!(PatternFormatter::new("%1024m").is_ok());
+}
+
+#[test]
+fn test_logfmt_formatter() {
+    let formatter = LogfmtFormatter {
+        include_timestamp: false,
+        ..Default::default()
+    };
+    let entry = LogEntry::new(LogLevel::Info, "app".to_string(), "user saved".to_string())
+        .with_metadata("user_id".to_string(), json!(42))
+        .with_metadata("path".to_string(), json!("/home"))
+        .with_metadata("query".to_string(), json!("a=\"b\"\nc"))
+        .with_metadata("empty".to_string(), json!(""))
+        .with_metadata("bad key".to_string(), json!(true));
+
+    assert_eq!(
+        formatter.format(&entry),
+        r#"level=info logger=app msg="user saved" bad_key=true empty="" path=/home query="a=\"b\"\nc" user_id=42"#
+    );
+
+    let with_ts = LogfmtFormatter::default().format(&entry);
+    assert!(with_ts.starts_with(&format!("ts={} ", entry.timestamp.to_rfc3339())));
+}
+
+#[test]
+fn test_syslog_formatter() {
+    let formatter = SyslogFormatter::new("my app")
+        .with_facility(SyslogFacility::Local0)
+        .with_hostname("web-1");
+    let entry = LogEntry::new(
+        LogLevel::Warn,
+        "http".to_string(),
+        "slow request".to_string(),
+    )
+    .with_metadata("path".to_string(), json!("/a\"b]"))
+    .with_metadata("ms".to_string(), json!(1200));
+
+    let line = formatter.format(&entry);
+    let timestamp = entry.timestamp.to_rfc3339_opts(chrono::SecondsFormat::Micros, true);
+    assert_eq!(
+        line,
+        format!(
+            r#"<132>1 {timestamp} web-1 myapp {} http [meta@32473 ms="1200" path="/a\"b\]"] slow request"#,
+            std::process::id()
+        )
+    );
+
+    let plain = LogEntry::new(LogLevel::Debug, String::new(), "x".to_string());
+    let line = SyslogFormatter::new("app").with_hostname("h").format(&plain);
+    assert!(line.starts_with("<15>1 "));
+    assert!(line.ends_with(" - - x"));
+}
+
+#[test]
+fn test_syslog_severity_mapping() {
+    assert_eq!(syslog_severity(LogLevel::Trace), 7);
+    assert_eq!(syslog_severity(LogLevel::Info), 6);
+    assert_eq!(syslog_severity(LogLevel::Warn), 4);
+    assert_eq!(syslog_severity(LogLevel::Error), 3);
+}
+
+#[test]
+fn test_colored_formatter() {
+    let entry = LogEntry::new(LogLevel::Error, "app".to_string(), "boom".to_string());
+
+    let plain = ColoredFormatter::new(ColorMode::Never);
+    assert!(!plain.colors_enabled());
+    assert_eq!(
+        plain.format(&entry),
+        SimpleFormatter::default().format(&entry)
+    );
+
+    let colored = ColoredFormatter::new(ColorMode::Always).format(&entry);
+    assert!(colored.contains(&format!("{}[ERROR]\x1b[0m", level_color(LogLevel::Error))));
+    assert!(colored.starts_with("\x1b[2m"));
+    assert!(colored.ends_with("(app) boom"));
+}
+
+#[test]
+fn test_color_auto_disabled_without_terminal() {
+    // Test harness output is captured, so stdout is never a terminal here
+    if !std::io::IsTerminal::is_terminal(&std::io::stdout()) {
+        assert!(!ColorMode::Auto.enabled());
+        assert!(!ColoredFormatter::default().colors_enabled());
+    }
+}
+
+#[test]
+fn test_split_console_output_routing() {
+    let output = SplitConsoleOutput::default();
+    assert!(output.uses_stderr(LogLevel::Warn));
+    assert!(output.uses_stderr(LogLevel::Error));
+    assert!(!output.uses_stderr(LogLevel::Info));
+    assert!(!SplitConsoleOutput::new(LogLevel::Error).uses_stderr(LogLevel::Warn));
+}
+
+#[test]
+fn test_split_console_output_colors_per_stream() {
+    let never = SplitConsoleOutput::default().with_color(ColorMode::Never);
+    assert!(!never.colors_enabled(LogLevel::Info));
+    assert!(!never.colors_enabled(LogLevel::Error));
+
+    let always = SplitConsoleOutput::default().with_color(ColorMode::Always);
+    assert!(always.colors_enabled(LogLevel::Info));
+    assert!(always.colors_enabled(LogLevel::Error));
+
+    let auto = SplitConsoleOutput::default();
+    assert_eq!(
+        auto.colors_enabled(LogLevel::Info),
+        ColorMode::Auto.enabled()
+    );
+    assert_eq!(
+        auto.colors_enabled(LogLevel::Error),
+        ColorMode::Auto.enabled_for_stderr()
+    );
+}
+
+#[test]
+fn test_strip_ansi() {
+    let entry = LogEntry::new(LogLevel::Warn, "app".to_string(), "slow".to_string());
+    let colored = ColoredFormatter::new(ColorMode::Always).format(&entry);
+    assert_eq!(
+        strip_ansi(&colored),
+        SimpleFormatter::default().format(&entry)
+    );
+    assert_eq!(strip_ansi("plain"), "plain");
+}
+
+/// Output that records the level seen by `write_entry`
+#[derive(Clone, Default)]
+struct LevelOutput {
+    levels: Arc<Mutex<Vec<Option<LogLevel>>>>,
+}
+
+impl LogOutput for LevelOutput {
+    fn write(&self, _formatted_message: &str) {
+        self.levels.lock().unwrap().push(None);
+    }
+
+    fn write_entry(&self, entry: &LogEntry, _formatted_message: &str) {
+        self.levels.lock().unwrap().push(Some(entry.level));
+    }
+}
+
+#[test]
+fn test_async_output_forwards_entries() {
+    let inner = LevelOutput::default();
+    let output = Arc::new(AsyncOutput::new(Arc::new(inner.clone())));
+    let logger = Logger::new(LoggerConfig::new("fwd".to_string()).with_output(output.clone()));
+
+    logger.warn("entry");
+    output.write("raw");
+    output.flush();
+
+    assert_eq!(
+        *inner.levels.lock().unwrap(),
+        vec![Some(LogLevel::Warn), None]
+    );
+}
+
+#[test]
+fn test_memory_output_queries() {
+    let output = Arc::new(MemoryOutput::default());
+    let config = LoggerConfig::new("mem".to_string())
+        .with_level(LogLevel::Debug)
+        .with_output(output.clone());
+    let logger = Logger::new(config);
+    let other = Logger::new(LoggerConfig::new("other".to_string()).with_output(output.clone()));
+
+    logger.debug("cache miss");
+    let mut metadata = HashMap::new();
+    metadata.insert("user_id".to_string(), json!(7));
+    logger.log_with_metadata(LogLevel::Warn, "slow login", metadata);
+    other.error("db down");
+
+    assert_eq!(output.len(), 3);
+    assert_eq!(output.by_level(LogLevel::Debug).len(), 1);
+    assert_eq!(output.by_logger("other")[0].message, "db down");
+    assert_eq!(output.containing("login").len(), 1);
+    assert_eq!(output.with_metadata_key("user_id")[0].level, LogLevel::Warn);
+    assert_eq!(
+        output.query(&LogQuery::new().min_level(LogLevel::Warn)).len(),
+        2
+    );
+    assert_eq!(
+        output.messages(),
+        vec!["cache miss", "slow login", "db down"]
+    );
+
+    output.assert_logged(&LogQuery::new().metadata_eq("user_id", json!(7)));
+    output.assert_not_logged(&LogQuery::new().logger("mem").level(LogLevel::Error));
+    output.assert_count(&LogQuery::new().logger("mem"), 2);
+
+    output.clear();
+    assert!(output.is_empty());
+}
+
+#[test]
+fn test_memory_output_ring_buffer() {
+    let output = MemoryOutput::new(2);
+    for i in 0..5 {
+        let entry = LogEntry::new(LogLevel::Info, "ring".to_string(), format!("m{i}"));
+        output.write_entry(&entry, &entry.message);
+    }
+    output.write("raw line");
+
+    assert_eq!(output.formatted(), vec!["m4", "raw line"]);
+    assert_eq!(output.messages(), vec!["m4"]);
+}
+
+#[test]
+#[should_panic(expected = "expected an entry matching `level == ERROR")]
+fn test_memory_output_assert_logged_panics() {
+    let output = MemoryOutput::default();
+    let entry = LogEntry::new(LogLevel::Info, "x".to_string(), "fine".to_string());
+    output.write_entry(&entry, "fine");
+    output.assert_logged(&LogQuery::new().level(LogLevel::Error));
+}
+
+#[test]
+fn test_multi_output_per_sink_levels_and_formatters() {
+    let console = Arc::new(MemoryOutput::default());
+    let errors = Arc::new(MemoryOutput::default());
+    let output = MultiOutput::new()
+        .with_sink(console.clone(), LogLevel::Debug)
+        .with_formatted_sink(errors.clone(), LogLevel::Error, Arc::new(JsonFormatter));
+    assert_eq!(output.sinks().len(), 2);
+
+    let logger = Logger::new(
+        LoggerConfig::new("tee".to_string())
+            .with_level(LogLevel::Debug)
+            .with_output(Arc::new(output)),
+    );
+
+    logger.debug("details");
+    logger.error("failure");
+
+    let console_lines = console.formatted();
+    assert_eq!(console_lines.len(), 2);
+    assert!(console_lines[1].contains("[ERROR] (tee) failure"));
+
+    let error_lines = errors.formatted();
+    assert_eq!(error_lines.len(), 1);
+    let parsed: Value = serde_json::from_str(&error_lines[0]).unwrap();
+    assert_eq!(parsed["message"], "failure");
+    assert_eq!(errors.entries()[0].level, LogLevel::Error);
+}
+
+#[test]
+fn test_multi_output_raw_write_reaches_all_sinks() {
+    let a = Arc::new(TestOutput::new());
+    let b = Arc::new(TestOutput::new());
+    let output = MultiOutput::new()
+        .with_sink(a.clone(), LogLevel::Trace)
+        .with_sink(b.clone(), LogLevel::Error);
+
+    output.write("raw");
+    assert_eq!(a.get_messages(), vec!["raw"]);
+    assert_eq!(b.get_messages(), vec!["raw"]);
+}
+
+#[test]
+fn test_sampling_first_then_one_in_m() {
+    let output = Arc::new(MemoryOutput::default());
+    let logger = Logger::new(
+        LoggerConfig::new("sampled".to_string())
+            .with_output(output.clone())
+            .with_sampling(SamplingPolicy {
+                interval: std::time::Duration::from_secs(60),
+                first: 3,
+                thereafter: 5,
+                ..Default::default()
+            }),
+    );
+
+    for _ in 0..20 {
+        logger.warn("disk full");
+    }
+    logger.warn("other message");
+
+    // 3 first + entries 8, 13, 18 + the unrelated message
+    assert_eq!(output.containing("disk full").len(), 6);
+    assert_eq!(output.containing("other").len(), 1);
+
+    logger.flush();
+    let summary = output.with_metadata_key("suppressed");
+    assert_eq!(summary.len(), 1);
+    assert_eq!(summary[0].metadata["suppressed"], json!(14));
+    assert_eq!(summary[0].metadata["sampled_message"], json!("disk full"));
+    assert_eq!(summary[0].level, LogLevel::Warn);
+}
+
+#[test]
+fn test_rate_limit_emits_summary_after_interval() {
+    let output = Arc::new(MemoryOutput::default());
+    let logger = Logger::new(
+        LoggerConfig::new("limited".to_string())
+            .with_output(output.clone())
+            .with_sampling(SamplingPolicy::rate_limit(
+                2,
+                std::time::Duration::from_millis(30),
+            )),
+    );
+
+    for _ in 0..10 {
+        logger.info("tick");
+    }
+    assert_eq!(output.len(), 2);
+
+    std::thread::sleep(std::time::Duration::from_millis(40));
+    logger.info("tick");
+
+    let messages = output.messages();
+    assert_eq!(messages.len(), 4);
+    assert_eq!(messages[2], "suppressed 8 similar log entries");
+    assert_eq!(messages[3], "tick");
+}
+
+#[test]
+fn test_rate_limit_summarizes_burst_that_stopped() {
+    let output = Arc::new(MemoryOutput::default());
+    let logger = Logger::new(
+        LoggerConfig::new("burst".to_string())
+            .with_output(output.clone())
+            .with_sampling(SamplingPolicy::rate_limit(
+                1,
+                std::time::Duration::from_millis(30),
+            )),
+    );
+
+    for _ in 0..5 {
+        logger.warn("connection reset");
+    }
+    std::thread::sleep(std::time::Duration::from_millis(40));
+    // A different message reports the burst that ended in the meantime
+    logger.info("heartbeat");
+
+    let messages = output.messages();
+    assert_eq!(
+        messages,
+        vec![
+            "connection reset",
+            "suppressed 4 similar log entries",
+            "heartbeat"
+        ]
+    );
+    let summary = output.with_metadata_key("suppressed");
+    assert_eq!(summary[0].level, LogLevel::Warn);
+
+    logger.flush();
+    assert_eq!(output.len(), 3);
+}
+
+#[test]
+fn test_sampling_by_call_site() {
+    let output = Arc::new(MemoryOutput::default());
+    let logger = Logger::new(
+        LoggerConfig::new("callsite".to_string())
+            .with_output(output.clone())
+            .with_sampling(SamplingPolicy {
+                key: SampleKey::CallSite,
+                ..SamplingPolicy::rate_limit(1, std::time::Duration::from_secs(60))
+            }),
+    );
+
+    for i in 0..5 {
+        mudssky_utils::info!(logger, "row {}", i);
+    }
+    assert_eq!(output.messages(), vec!["row 0"]);
+}
+
+#[test]
+fn test_redactor_masks_keys_and_messages() {
+    let output = Arc::new(MemoryOutput::default());
+    let logger = Logger::new(
+        LoggerConfig::new("redact".to_string())
+            .with_formatter(Arc::new(JsonFormatter))
+            .with_output(output.clone())
+            .with_redactor(Redactor::default()),
+    );
+
+    let mut metadata = HashMap::new();
+    metadata.insert("Authorization".to_string(), json!("Bearer abc"));
+    metadata.insert("user_password".to_string(), json!("hunter2"));
+    metadata.insert(
+        "payload".to_string(),
+        json!({"access_token": "t", "contact": ["x@y.com", 13812345678u64], "note": "ok"}),
+    );
+    logger.log_with_metadata(
+        LogLevel::Info,
+        "user bob@example.com phone 13812345678 card 4111111111111111",
+        metadata,
+    );
+
+    let entry = &output.entries()[0];
+    assert_eq!(
+        entry.message,
+        "user [REDACTED] phone [REDACTED] card [REDACTED]"
+    );
+    assert_eq!(entry.metadata["Authorization"], json!("[REDACTED]"));
+    assert_eq!(entry.metadata["user_password"], json!("[REDACTED]"));
+    assert_eq!(
+        entry.metadata["payload"]["access_token"],
+        json!("[REDACTED]")
+    );
+    assert_eq!(entry.metadata["payload"]["contact"][0], json!("[REDACTED]"));
+    assert_eq!(entry.metadata["payload"]["note"], json!("ok"));
+    assert!(!output.formatted()[0].contains("hunter2"));
+}
+
+#[test]
+fn test_redactor_default_keys_match_whole_segments() {
+    let redactor = Redactor::default();
+    for key in [
+        "password",
+        "DB_PASSWORD",
+        "user-passwd",
+        "pass",
+        "client.secret",
+        "access_token",
+        "accessToken",
+        "Authorization",
+        "x-api-key",
+        "apiKey",
+        "sessionCookie",
+        "cookie_header",
+    ] {
+        assert!(redactor.is_sensitive_key(key), "{key} should be masked");
+    }
+    for key in [
+        "bypass",
+        "compass",
+        "passenger_count",
+        "token_count",
+        "max_tokens",
+        "maxTokens",
+        "secretary",
+        "cookies_enabled",
+    ] {
+        assert!(
+            !redactor.is_sensitive_key(key),
+            "{key} should not be masked"
+        );
+    }
+}
+
+#[test]
+fn test_redactor_custom_rules_and_context() {
+    let redactor = Redactor::new()
+        .with_key_pattern("^tenant$")
+        .unwrap()
+        .with_key_mask("***")
+        .with_detector(
+            Detector::Pattern(regex::Regex::new(r"id-(\d{2})\d+").unwrap()),
+            "id-$1**",
+        );
+    assert!(redactor.is_sensitive_key("TENANT"));
+    assert!(!redactor.is_sensitive_key("tenant_name"));
+    assert_eq!(redactor.redact_text("order id-12345"), "order id-12**");
+
+    let output = Arc::new(MemoryOutput::default());
+    let logger = Logger::new(
+        LoggerConfig::new("redact_ctx".to_string())
+            .with_output(output.clone())
+            .with_redactor(redactor),
+    );
+    let _guard = push_context([("tenant", json!("acme"))]);
+    logger.info("plain");
+    assert_eq!(output.entries()[0].metadata["tenant"], json!("***"));
+}
+
+#[test]
+fn test_span_logs_enter_and_exit() {
+    let output = Arc::new(MemoryOutput::default());
+    let logger = Logger::new(LoggerConfig::new("span".to_string()).with_output(output.clone()));
+
+    let span = logger.span("load");
+    let id = span.id();
+    std::thread::sleep(std::time::Duration::from_millis(5));
+    span.finish();
+
+    let entries = output.entries();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].message, "load started");
+    assert_eq!(entries[1].metadata["span_id"], json!(id));
+    assert_eq!(entries[1].metadata["outcome"], json!("ok"));
+    assert!(entries[1].metadata["elapsed_ms"].as_f64().unwrap() >= 5.0);
+}
+
+#[test]
+fn test_time_records_error_and_nesting() {
+    let output = Arc::new(MemoryOutput::default());
+    let logger = Logger::new(LoggerConfig::new("timed".to_string()).with_output(output.clone()));
+    let exit_only = SpanOptions {
+        log_enter: false,
+        ..Default::default()
+    };
+
+    let result: Result<(), String> = logger.time("outer", || {
+        let inner = logger.span_with("inner", exit_only.clone());
+        logger.info("working");
+        drop(inner);
+        Err("disk full".to_string())
+    });
+    assert!(result.is_err());
+
+    let entries = output.entries();
+    let outer_id = entries[0].metadata["span_id"].clone();
+    let inner = &output.containing("inner finished")[0];
+    assert_eq!(inner.metadata["parent_span_id"], outer_id);
+    assert_eq!(
+        output.containing("working")[0].metadata["span_id"],
+        outer_id
+    );
+
+    let failed = &output.containing("outer failed")[0];
+    assert_eq!(failed.level, LogLevel::Error);
+    assert_eq!(failed.metadata["outcome"], json!("error"));
+    assert_eq!(failed.metadata["error"], json!("disk full"));
+}
+
+#[tokio::test]
+async fn test_time_async_across_await() {
+    let output = Arc::new(MemoryOutput::default());
+    let logger =
+        Logger::new(LoggerConfig::new("timed_async".to_string()).with_output(output.clone()));
+
+    let value = logger
+        .time_async("fetch", async {
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            logger.time_async("parse", async { Ok::<_, String>(7) }).await
+        })
+        .await
+        .unwrap();
+    assert_eq!(value, 7);
+
+    let fetch = &output.containing("fetch finished")[0];
+    let parse = &output.containing("parse finished")[0];
+    assert_eq!(parse.metadata["parent_span_id"], fetch.metadata["span_id"]);
+    assert!(fetch.metadata["elapsed_ms"].as_f64().unwrap() >= 10.0);
+    assert!(!fetch.metadata.contains_key("parent_span_id"));
+}
+
+#[test]
+fn test_span_dropped_by_panic_logs_panicked() {
+    let output = Arc::new(MemoryOutput::default());
+    let logger =
+        Logger::new(LoggerConfig::new("span_panic".to_string()).with_output(output.clone()));
+
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        let _ = logger.time("import", || -> Result<(), String> { panic!("bad row") });
+    }));
+    assert!(result.is_err());
+
+    let entries = output.entries();
+    assert_eq!(entries.len(), 2);
+    assert!(entries[1].message.starts_with("import panicked after"));
+    assert_eq!(entries[1].level, LogLevel::Error);
+    assert_eq!(entries[1].metadata["outcome"], json!("panicked"));
+}
+
+#[tokio::test]
+async fn test_time_async_cancelled() {
+    let output = Arc::new(MemoryOutput::default());
+    let logger =
+        Logger::new(LoggerConfig::new("span_cancel".to_string()).with_output(output.clone()));
+
+    let slow = logger.time_async("slow", async {
+        tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+        Ok::<_, String>(())
+    });
+    let timed_out = tokio::time::timeout(std::time::Duration::from_millis(10), slow).await;
+    assert!(timed_out.is_err());
+
+    let entries = output.entries();
+    assert_eq!(entries.len(), 2);
+    assert!(entries[1].message.starts_with("slow cancelled after"));
+    assert_eq!(entries[1].level, LogLevel::Warn);
+    assert_eq!(entries[1].metadata["outcome"], json!("cancelled"));
+}
+
+#[derive(Debug)]
+struct WrappedError {
+    source: mudssky_utils::error::UtilsError,
+}
+
+impl std::fmt::Display for WrappedError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "failed to save user")
+    }
+}
+
+impl std::error::Error for WrappedError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+#[test]
+fn test_error_with_records_chain_and_fields() {
+    use mudssky_utils::error::{UtilsError, ValidationError};
+
+    let output = Arc::new(MemoryOutput::default());
+    let logger = Logger::new(
+        LoggerConfig::new("errors".to_string())
+            .with_formatter(Arc::new(JsonFormatter))
+            .with_output(output.clone()),
+    );
+
+    let error = WrappedError {
+        source: UtilsError::from(ValidationError::with_value("email", "invalid", "x@")),
+    };
+    logger.error_with("save failed", &error);
+
+    let parsed: Value = serde_json::from_str(&output.formatted()[0]).unwrap();
+    assert_eq!(parsed["level"], "ERROR");
+    assert_eq!(parsed["error"], "failed to save user");
+    assert_eq!(parsed["error_chain"][0], "failed to save user");
+    assert!(parsed["error_chain"][1].as_str().unwrap().contains("email"));
+    assert_eq!(parsed["error_kind"], "Validation");
+    assert_eq!(parsed["error_field"], "email");
+    assert_eq!(parsed["error_value"], "x@");
+}
+
+#[test]
+fn test_error_metadata_kinds() {
+    use mudssky_utils::error::{ConfigError, NetworkError, ParseError, UtilsError};
+
+    let network = error_metadata(&NetworkError::with_status("fetch", "not found", 404));
+    assert_eq!(network["error_kind"], json!("Network"));
+    assert_eq!(network["error_operation"], json!("fetch"));
+    assert_eq!(network["error_status_code"], json!(404));
+
+    let parse = error_metadata(&UtilsError::from(ParseError::with_position(
+        "1x", "digit", 1,
+    )));
+    assert_eq!(parse["error_kind"], json!("Parse"));
+    assert_eq!(parse["error_position"], json!(1));
+
+    let config = error_metadata(&ConfigError::new("db.url", "missing"));
+    assert_eq!(config["error_key"], json!("db.url"));
+
+    let io = error_metadata(&UtilsError::from(std::io::Error::new(
+        std::io::ErrorKind::NotFound,
+        "gone",
+    )));
+    assert_eq!(io["error_kind"], json!("Io"));
+    assert_eq!(io["error_io_kind"], json!("NotFound"));
+
+    let plain = error_metadata(&std::fmt::Error);
+    assert!(!plain.contains_key("error_kind"));
+    assert_eq!(plain["error_chain"].as_array().unwrap().len(), 1);
+}
@@ -118,3 +118,14 @@ fn test_matches_pattern() {
     assert!(matches_pattern("hello123", r"^[a-z]+\d+$").unwrap());
     assert!(!matches_pattern("Hello123", r"^[a-z]+\d+$").unwrap());
 }
+
+#[test]
+fn test_to_search_pattern() {
+    let email = to_search_pattern(&get_patterns().email).unwrap();
+    let found: Vec<&str> =
+        email.find_iter("mail a@b.com or c.d@e.org.").map(|m| m.as_str()).collect();
+    assert_eq!(found, vec!["a@b.com", "c.d@e.org"]);
+
+    let card = to_search_pattern(&get_patterns().credit_card).unwrap();
+    assert!(card.is_match("card 4111111111111111 charged"));
+}